@@ -0,0 +1,29 @@
+#version 100
+precision mediump float;
+
+varying lowp vec2 uv;
+varying lowp vec2 light;
+varying lowp float sun;
+varying highp float distance;
+
+uniform sampler2D Texture;
+
+uniform vec4 FogColor;
+uniform float FogStart;
+uniform float FogEnd;
+uniform vec3 SkyTint;
+
+const vec3 BLOCK_TINT = vec3(1.0, 0.9, 0.75);
+const float MIN_LIGHT = 0.04;
+
+void main() {
+    vec4 albedo = texture2D(Texture, uv);
+
+    vec3 sky = light.x * SkyTint * (0.85 + 0.15 * sun);
+    vec3 block = light.y * BLOCK_TINT;
+    vec3 color = albedo.rgb * max(max(sky, block), vec3(MIN_LIGHT));
+
+    float fog = clamp((distance - FogStart) / (FogEnd - FogStart), 0.0, 1.0);
+
+    gl_FragColor = vec4(mix(color, FogColor.rgb, fog), albedo.a);
+}
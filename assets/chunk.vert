@@ -0,0 +1,29 @@
+#version 100
+attribute vec3 position;
+attribute vec2 texcoord;
+attribute vec4 color0;
+attribute vec4 normal;
+
+varying lowp vec2 uv;
+varying lowp vec2 light;
+varying lowp float sun;
+varying highp float distance;
+
+uniform mat4 Model;
+uniform mat4 Projection;
+
+uniform vec3 CameraPos;
+uniform vec3 SunDirection;
+
+void main() {
+    gl_Position = Projection * Model * vec4(position, 1);
+    uv = texcoord;
+
+    // Red is sky light, green is block light
+    light = color0.rg / 255.0;
+
+    sun = max(dot(normal.xyz, SunDirection), 0.0);
+
+    // Fog is cylindrical, so looking up or down doesn't change it
+    distance = length(position.xz - CameraPos.xz);
+}
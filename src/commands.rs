@@ -0,0 +1,293 @@
+use macroquad::prelude::*;
+
+use crate::inventory::*;
+use crate::item::*;
+use crate::player::*;
+use crate::schematic::*;
+use crate::world::generator::*;
+use crate::world::time::*;
+use crate::world::*;
+
+/// Coordinates further from the origin are refused
+pub const MAX_COORDINATE: f32 = 30_000_000.0;
+
+/// Most blocks a single `fill`, `replace`, `clone` or `hollow` may change
+pub const MAX_FILL_VOLUME: usize = 32 * 1024;
+
+#[rustfmt::skip]
+pub const COMMANDS: [&str; 11] = ["tp", "time", "give", "fill", "replace", "clone", "hollow", "schematic", "seed", "gamemode", "help"];
+
+const TIMES_OF_DAY: [&str; 6] = ["sunrise", "day", "noon", "sunset", "night", "midnight"];
+
+/// State commands can read and change, borrowed from the running game or built headless
+pub struct CommandContext<'a> {
+    pub world: &'a mut World,
+    pub player: &'a mut PlayerController,
+    pub inventory: &'a mut Inventory,
+    pub generator: Generator,
+    pub game_mode: &'a mut GameMode,
+    pub schematics: &'a Schematics,
+}
+
+/// Runs a command, the leading `/` is optional. Returns the message to show.
+/// Coordinates starting with `~` are relative to the player.
+///
+/// ```
+/// # use cubic_game::commands::*;
+/// # use cubic_game::inventory::*;
+/// # use cubic_game::player::*;
+/// # use cubic_game::schematic::*;
+/// # use cubic_game::world::*;
+/// # use cubic_game::world::generator::*;
+/// # use cubic_game::world::time::*;
+/// # use macroquad::prelude::*;
+/// let mut world = World::new();
+/// world.insert_chunk(ChunkPos::new(0, 0, 0), Chunk::EMPTY);
+///
+/// let mut player = PlayerController::new(Vec3::ZERO);
+/// let mut inventory = Inventory::default();
+/// let mut game_mode = GameMode::Survival;
+///
+/// let mut ctx = CommandContext {
+///     world: &mut world,
+///     player: &mut player,
+///     inventory: &mut inventory,
+///     generator: Generator { kind: GeneratorKind::Flat, seed: 99 },
+///     game_mode: &mut game_mode,
+///     schematics: &Schematics::new(SCHEMATICS_DIR),
+/// };
+///
+/// run_command(&mut ctx, "time set night").unwrap();
+/// assert_eq!(ctx.world.time(), WorldTime(NIGHT));
+///
+/// // Time never goes back
+/// run_command(&mut ctx, "time set noon").unwrap();
+/// assert_eq!(ctx.world.time(), WorldTime(TICKS_PER_DAY + NOON));
+///
+/// assert!(run_command(&mut ctx, "time set later").is_err());
+/// assert!(run_command(&mut ctx, "time add 18446744073709551615").is_err());
+///
+/// run_command(&mut ctx, "/tp 2 5 ~3").unwrap();
+/// assert_eq!(ctx.player.pos.0, vec3(2.0, 5.0, 3.0));
+///
+/// run_command(&mut ctx, "/fill 1 1 1 ~ ~ 3 Stone").unwrap();
+/// assert_eq!(ctx.world.block(BlockPos::new(2, 5, 3)), &BlockState::STONE);
+/// assert_eq!(ctx.world.block(BlockPos::new(2, 6, 3)), &BlockState::AIR);
+///
+/// run_command(&mut ctx, "/replace 0 0 0 15 15 15 Stone Dirt").unwrap();
+/// run_command(&mut ctx, "/clone 1 1 1 2 5 3 ~ 6 ~").unwrap();
+/// assert_eq!(ctx.world.block(BlockPos::new(3, 10, 4)), &BlockState::DIRT);
+/// assert!(run_command(&mut ctx, "/hollow 0 0 0 100 100 100 Stone").is_err());
+/// assert!(run_command(&mut ctx, "/fill 0 0 0 10000000 10000000 10000000 Stone").is_err());
+/// assert!(run_command(&mut ctx, "/fill -1e30 0 0 1e30 0 0 Stone").is_err());
+/// assert!(run_command(&mut ctx, "/tp nan 0 inf").is_err());
+///
+/// run_command(&mut ctx, "/give Torch 70").unwrap();
+/// assert_eq!(ctx.inventory.slots[1].unwrap().count, 6);
+///
+/// run_command(&mut ctx, "/gamemode creative").unwrap();
+/// assert_eq!(*ctx.game_mode, GameMode::Creative);
+///
+/// assert_eq!(run_command(&mut ctx, "/seed"), Ok(String::from("Seed: 99")));
+/// ```
+pub fn run_command(ctx: &mut CommandContext, command: &str) -> Result<String, String> {
+    let command = command.trim().trim_start_matches('/');
+    let args: Vec<&str> = command.split_whitespace().collect();
+
+    match args.as_slice() {
+        ["tp", x, y, z] => {
+            let origin = ctx.player.pos.0;
+            let pos = vec3(parse_coordinate(x, origin.x)?, parse_coordinate(y, origin.y)?, parse_coordinate(z, origin.z)?);
+            ctx.player.pos.0 = pos;
+            Ok(format!("Teleported to {:.1} {:.1} {:.1}", pos.x, pos.y, pos.z))
+        }
+        ["tp", ..] => Err(String::from("usage: tp <x> <y> <z>")),
+        ["time", "query"] => Ok(describe_time(ctx.world.time())),
+        ["time", "set", value] => {
+            let mut time = ctx.world.time();
+            time.set_time_of_day(parse_time_of_day(value)?);
+            ctx.world.set_time(time);
+            Ok(describe_time(time))
+        }
+        ["time", "add", ticks] => {
+            let ticks: u64 = ticks.parse().map_err(|_| format!("expected a number of ticks, got `{}`", ticks))?;
+            let time = ctx.world.time().0.checked_add(ticks).ok_or("time would overflow")?;
+            ctx.world.set_time(WorldTime(time));
+            Ok(describe_time(ctx.world.time()))
+        }
+        ["time", ..] => Err(String::from("usage: time query | time set <ticks|sunrise|day|noon|sunset|night|midnight> | time add <ticks>")),
+        ["give", item, count @ ..] if count.len() <= 1 => {
+            let item: Item = item.parse()?;
+            let count: u32 = match count.first() {
+                Some(count) => count.parse().map_err(|_| format!("expected a number of items, got `{}`", count))?,
+                None => 1,
+            };
+
+            let mut left = count;
+            while left > 0 {
+                let stack = ItemStack::new(item, left.min(item.max_stack_size()));
+                let leftover = ctx.inventory.add(stack);
+                left -= stack.count - leftover;
+                if leftover > 0 {
+                    return Err(format!("Gave {} {}, {} didn't fit", count - left, item, left));
+                }
+            }
+            Ok(format!("Gave {} {}", count, item))
+        }
+        ["give", ..] => Err(String::from("usage: give <item> [count]")),
+        ["fill", x1, y1, z1, x2, y2, z2, block] => {
+            let region = parse_region(ctx, [x1, y1, z1, x2, y2, z2])?;
+            let block_type: BlockType = block.parse()?;
+            let changed = ctx.world.fill(region, BlockState::new(block_type));
+            Ok(format!("Filled {} blocks with {}", changed, block_type))
+        }
+        ["fill", ..] => Err(String::from("usage: fill <x1> <y1> <z1> <x2> <y2> <z2> <block>")),
+        ["replace", x1, y1, z1, x2, y2, z2, from, to] => {
+            let region = parse_region(ctx, [x1, y1, z1, x2, y2, z2])?;
+            let (from, to): (BlockType, BlockType) = (from.parse()?, to.parse()?);
+            let changed = ctx.world.replace(region, from, BlockState::new(to));
+            Ok(format!("Replaced {} blocks of {} with {}", changed, from, to))
+        }
+        ["replace", ..] => Err(String::from("usage: replace <x1> <y1> <z1> <x2> <y2> <z2> <from> <to>")),
+        ["clone", x1, y1, z1, x2, y2, z2, x, y, z] => {
+            let region = parse_region(ctx, [x1, y1, z1, x2, y2, z2])?;
+            let to = parse_block_pos(ctx, [x, y, z])?;
+            let changed = ctx.world.clone_region(region, to);
+            Ok(format!("Cloned {} blocks, {} changed", region.volume().unwrap_or_default(), changed))
+        }
+        ["clone", ..] => Err(String::from("usage: clone <x1> <y1> <z1> <x2> <y2> <z2> <x> <y> <z>")),
+        ["hollow", x1, y1, z1, x2, y2, z2, block] => {
+            let region = parse_region(ctx, [x1, y1, z1, x2, y2, z2])?;
+            let block_type: BlockType = block.parse()?;
+            let changed = ctx.world.hollow(region, BlockState::new(block_type));
+            Ok(format!("Hollowed out {} blocks with walls of {}", changed, block_type))
+        }
+        ["hollow", ..] => Err(String::from("usage: hollow <x1> <y1> <z1> <x2> <y2> <z2> <block>")),
+        ["schematic", "save", name, x1, y1, z1, x2, y2, z2] => {
+            let region = parse_region(ctx, [x1, y1, z1, x2, y2, z2])?;
+            let schematic = Schematic::from_world(ctx.world, region);
+            ctx.schematics.save(name, &schematic)?;
+            Ok(format!("Saved {} blocks to schematic `{}`", schematic.volume(), name))
+        }
+        ["schematic", "load", name, x, y, z, transform @ ..] if transform.len() <= 2 => {
+            let at = parse_block_pos(ctx, [x, y, z])?;
+            let rotation: Rotation = transform.first().map_or(Ok(Rotation::default()), |rotation| rotation.parse())?;
+            let mirror: Mirror = transform.get(1).map_or(Ok(Mirror::default()), |mirror| mirror.parse())?;
+
+            let schematic = ctx.schematics.load(name)?.transformed(rotation, mirror);
+            if schematic.volume() > MAX_FILL_VOLUME {
+                return Err(format!("{} blocks is too many, at most {} can be changed at once", schematic.volume(), MAX_FILL_VOLUME));
+            }
+            let changed = schematic.paste(ctx.world, at);
+            Ok(format!("Pasted schematic `{}`, {} blocks changed", name, changed))
+        }
+        ["schematic", "list"] => match ctx.schematics.list() {
+            names if names.is_empty() => Ok(String::from("No schematics saved")),
+            names => Ok(format!("Schematics: {}", names.join(", "))),
+        },
+        ["schematic", ..] => Err(String::from(
+            "usage: schematic save <name> <x1> <y1> <z1> <x2> <y2> <z2> | schematic load <name> <x> <y> <z> [0|90|180|270] [none|x|z] | schematic list",
+        )),
+        ["seed"] => Ok(format!("Seed: {}", ctx.generator.seed)),
+        ["gamemode"] => Ok(format!("Game mode: {}", ctx.game_mode)),
+        ["gamemode", mode] => {
+            *ctx.game_mode = mode.parse()?;
+            Ok(format!("Game mode set to {}", ctx.game_mode))
+        }
+        ["gamemode", ..] => Err(String::from("usage: gamemode [survival|creative|spectator]")),
+        ["help", ..] => Ok(format!("Commands: {}", COMMANDS.join(", "))),
+        [name, ..] => Err(format!("unknown command `{}`", name)),
+        [] => Err(String::from("empty command")),
+    }
+}
+
+/// Words which can follow the typed text, the last word of `input` is the one being completed.
+///
+/// ```
+/// # use cubic_game::commands::*;
+/// assert_eq!(complete_command("/ti"), ["time"]);
+/// assert_eq!(complete_command("time set n"), ["noon", "night"]);
+/// assert_eq!(complete_command("gamemode "), ["Survival", "Creative", "Spectator"]);
+/// assert!(complete_command("fill 0 0 0 1 1 1 Gr").contains(&String::from("Gravel")));
+/// assert_eq!(complete_command("replace 0 0 0 1 1 1 Dirt Wa"), ["Water"]);
+/// assert_eq!(complete_command("schematic load hut ~ ~ ~ 1"), ["180"]);
+/// ```
+pub fn complete_command(input: &str) -> Vec<String> {
+    let input = input.trim_start().trim_start_matches('/');
+    let mut words: Vec<&str> = input.split(' ').collect();
+    let last = words.pop().unwrap_or_default();
+
+    let candidates: Vec<String> = match words.as_slice() {
+        [] => COMMANDS.map(String::from).to_vec(),
+        ["time"] => ["query", "set", "add"].map(String::from).to_vec(),
+        ["time", "set"] => TIMES_OF_DAY.map(String::from).to_vec(),
+        ["give"] => registered_items().map(|item| item.to_string()).collect(),
+        ["fill" | "hollow", _, _, _, _, _, _] | ["replace", _, _, _, _, _, _, ..] => BlockType::ALL.map(|block_type| block_type.to_string()).to_vec(),
+        ["schematic"] => ["save", "load", "list"].map(String::from).to_vec(),
+        ["schematic", "load", _, _, _, _] => Rotation::ALL.map(|rotation| rotation.to_string()).to_vec(),
+        ["schematic", "load", _, _, _, _, _] => Mirror::ALL.map(|mirror| mirror.to_string()).to_vec(),
+        ["gamemode"] => GameMode::ALL.map(|mode| mode.to_string()).to_vec(),
+        _ => Vec::new(),
+    };
+
+    candidates
+        .into_iter()
+        .filter(|candidate| candidate.to_lowercase().starts_with(&last.to_lowercase()))
+        .collect()
+}
+
+/// `12`, `~` or `~-3` relative to `origin`, at most [`MAX_COORDINATE`] away from the origin of the world
+fn parse_coordinate(arg: &str, origin: f32) -> Result<f32, String> {
+    let parse = |value: &str| value.parse::<f32>().map_err(|_| format!("expected a coordinate, got `{}`", arg));
+
+    let coordinate = match arg.strip_prefix('~') {
+        Some("") => origin,
+        Some(offset) => origin + parse(offset)?,
+        None => parse(arg)?,
+    };
+
+    match coordinate.is_finite() && coordinate.abs() <= MAX_COORDINATE {
+        true => Ok(coordinate),
+        false => Err(format!("coordinate `{}` is outside of the world, at most {} is allowed", arg, MAX_COORDINATE)),
+    }
+}
+
+fn parse_block_pos(ctx: &CommandContext, args: [&&str; 3]) -> Result<BlockPos, String> {
+    let origin = ctx.player.pos.0;
+    let pos = vec3(
+        parse_coordinate(args[0], origin.x)?,
+        parse_coordinate(args[1], origin.y)?,
+        parse_coordinate(args[2], origin.z)?,
+    );
+    Ok(BlockPos::from_vec3(pos))
+}
+
+/// Two corners of a box, refused if it is bigger than [`MAX_FILL_VOLUME`]
+fn parse_region(ctx: &CommandContext, args: [&&str; 6]) -> Result<BlockRegion, String> {
+    let from = parse_block_pos(ctx, [args[0], args[1], args[2]])?;
+    let to = parse_block_pos(ctx, [args[3], args[4], args[5]])?;
+    let region = BlockRegion::new(from, to);
+
+    match region.volume() {
+        Some(volume) if volume <= MAX_FILL_VOLUME => Ok(region),
+        Some(volume) => Err(format!("{} blocks is too many, at most {} can be changed at once", volume, MAX_FILL_VOLUME)),
+        None => Err(format!("region is too big, at most {} blocks can be changed at once", MAX_FILL_VOLUME)),
+    }
+}
+
+fn parse_time_of_day(value: &str) -> Result<u64, String> {
+    match value {
+        "sunrise" => Ok(SUNRISE),
+        "day" => Ok(DAY),
+        "noon" => Ok(NOON),
+        "sunset" => Ok(SUNSET),
+        "night" => Ok(NIGHT),
+        "midnight" => Ok(MIDNIGHT),
+        _ => value.parse().map_err(|_| format!("expected ticks or a time of day, got `{}`", value)),
+    }
+}
+
+fn describe_time(time: WorldTime) -> String {
+    format!("Day {}, time {}", time.day(), time.time_of_day())
+}
+
@@ -0,0 +1,62 @@
+use std::fmt::Display;
+
+/// One `key = value` line of a config file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConfigEntry<'a> {
+    pub line: usize,
+    pub key: &'a str,
+    pub value: &'a str,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl ConfigError {
+    pub fn new(line: usize, message: impl Into<String>) -> Self {
+        Self { line, message: message.into() }
+    }
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+/// Parses `key = value` lines. Empty lines and lines starting with `#` are skipped.
+/// Lines without `=` are reported and the rest of the file is still parsed.
+///
+/// ```
+/// # use cubic_game::config::*;
+/// let (entries, errors) = parse_config("# comment\nMoveForward = W, Up\n\nfov=70");
+///
+/// assert!(errors.is_empty());
+/// assert_eq!(entries[0].key, "MoveForward");
+/// assert_eq!(entries[0].value, "W, Up");
+/// assert_eq!(entries[1].line, 4);
+///
+/// let (entries, errors) = parse_config("no equals sign\nfov = 70");
+/// assert_eq!(errors[0].line, 1);
+/// assert_eq!(entries[0].key, "fov");
+/// ```
+pub fn parse_config(text: &str) -> (Vec<ConfigEntry<'_>>, Vec<ConfigError>) {
+    let mut entries = Vec::new();
+    let mut errors = Vec::new();
+
+    for (n, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        match line.split_once('=') {
+            Some((key, value)) => entries.push(ConfigEntry { line: n + 1, key: key.trim(), value: value.trim() }),
+            None => errors.push(ConfigError::new(n + 1, format!("expected `key = value`, got `{}`", line))),
+        }
+    }
+
+    (entries, errors)
+}
@@ -0,0 +1,151 @@
+use std::collections::VecDeque;
+
+use macroquad::prelude::*;
+
+use crate::commands::*;
+use crate::ui::Layout;
+
+/// Lines of output kept for the console
+pub const MAX_OUTPUT_LINES: usize = 10;
+
+/// Canvas units
+const FONT_SIZE: f32 = 8.0;
+
+#[rustfmt::skip]
+const BACKGROUND_COLOR: Color = Color { r: 0.0, g: 0.0, b: 0.0, a: 0.6 };
+
+/// Command line at the bottom of the screen with the recent output above it.
+///
+/// ```
+/// # use cubic_game::console::*;
+/// let mut console = Console::default();
+///
+/// console.type_text("/ti");
+/// console.complete();
+/// assert_eq!(console.input, "/time ");
+///
+/// console.type_text("query");
+/// assert_eq!(console.submit(), Some(String::from("/time query")));
+/// assert_eq!(console.input, "");
+///
+/// console.history_up();
+/// assert_eq!(console.input, "/time query");
+/// console.history_down();
+/// assert_eq!(console.input, "");
+/// ```
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Console {
+    pub open: bool,
+    pub input: String,
+    /// Submitted commands, oldest first
+    pub history: Vec<String>,
+    /// Entry of the history shown in the input, `None` while typing a new command
+    pub history_index: Option<usize>,
+    /// Oldest first
+    pub output: VecDeque<String>,
+}
+
+impl Console {
+    pub fn open(&mut self) {
+        self.open = true;
+        self.input.clear();
+        self.history_index = None;
+    }
+
+    pub fn close(&mut self) {
+        self.open = false;
+    }
+
+    pub fn type_text(&mut self, text: &str) {
+        self.input.push_str(text);
+        self.history_index = None;
+    }
+
+    pub fn backspace(&mut self) {
+        self.input.pop();
+    }
+
+    /// Takes the typed command and remembers it, `None` if nothing was typed
+    pub fn submit(&mut self) -> Option<String> {
+        let command = std::mem::take(&mut self.input);
+        self.history_index = None;
+
+        if command.trim().is_empty() {
+            return None;
+        }
+        if self.history.last() != Some(&command) {
+            self.history.push(command.clone());
+        }
+        Some(command)
+    }
+
+    pub fn print(&mut self, line: String) {
+        if self.output.len() == MAX_OUTPUT_LINES {
+            self.output.pop_front();
+        }
+        self.output.push_back(line);
+    }
+
+    /// Previous command from the history
+    pub fn history_up(&mut self) {
+        let index = match self.history_index {
+            Some(index) => index.saturating_sub(1),
+            None if self.history.is_empty() => return,
+            None => self.history.len() - 1,
+        };
+        self.history_index = Some(index);
+        self.input = self.history[index].clone();
+    }
+
+    /// Next command from the history, empty input after the last one
+    pub fn history_down(&mut self) {
+        let Some(index) = self.history_index else { return };
+
+        match self.history.get(index + 1) {
+            Some(command) => {
+                self.history_index = Some(index + 1);
+                self.input = command.clone();
+            }
+            None => {
+                self.history_index = None;
+                self.input.clear();
+            }
+        }
+    }
+
+    /// Completes the last word if only one word fits, otherwise prints what fits
+    pub fn complete(&mut self) {
+        let candidates = complete_command(&self.input);
+        let start = self.input.rfind([' ', '/']).map_or(0, |n| n + 1);
+
+        match candidates.as_slice() {
+            [] => {}
+            [word] => {
+                self.input.truncate(start);
+                self.input.push_str(word);
+                self.input.push(' ');
+            }
+            words => self.print(words.join(" ")),
+        }
+    }
+
+    /// Output above the input line, only while the console is open
+    pub fn draw(&self) {
+        if !self.open {
+            return;
+        }
+
+        let scale = Layout::screen().scale;
+        let font_size = FONT_SIZE * scale;
+        let line_height = font_size * 1.2;
+        let bottom = screen_height() - line_height * 3.0;
+
+        let lines = self.output.len() as f32 + 1.0;
+        draw_rectangle(0.0, bottom - line_height * lines, screen_width(), line_height * (lines + 0.4), BACKGROUND_COLOR);
+
+        for (n, line) in self.output.iter().rev().enumerate() {
+            draw_text(line, scale * 2.0, bottom - line_height * (n + 1) as f32, font_size, LIGHTGRAY);
+        }
+        draw_text(format!("> {}_", self.input).as_str(), scale * 2.0, bottom, font_size, WHITE);
+    }
+}
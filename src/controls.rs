@@ -0,0 +1,326 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::str::FromStr;
+
+use macroquad::prelude::*;
+
+use crate::config::*;
+
+pub const CONTROLS_PATH: &str = "controls.cfg";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    MoveForward,
+    MoveBackward,
+    MoveLeft,
+    MoveRight,
+    Jump,
+    Sneak,
+    /// Opens and closes the inventory, closes any other screen
+    Inventory,
+    /// Opens the pause menu, closes any other screen
+    Pause,
+    /// Opens the command console
+    OpenConsole,
+    Break,
+    Place,
+    PickBlock,
+    ToggleCameraMode,
+    ToggleDebug,
+    ToggleChunkBorders,
+    ToggleHitboxes,
+    IncreaseRenderDistance,
+    DecreaseRenderDistance,
+    HotbarSlot1,
+    HotbarSlot2,
+    HotbarSlot3,
+    HotbarSlot4,
+    HotbarSlot5,
+    HotbarSlot6,
+    HotbarSlot7,
+    HotbarSlot8,
+    HotbarSlot9,
+}
+
+impl Action {
+    pub const ALL: [Action; 27] = [
+        Action::MoveForward,
+        Action::MoveBackward,
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::Jump,
+        Action::Sneak,
+        Action::Inventory,
+        Action::Pause,
+        Action::OpenConsole,
+        Action::Break,
+        Action::Place,
+        Action::PickBlock,
+        Action::ToggleCameraMode,
+        Action::ToggleDebug,
+        Action::ToggleChunkBorders,
+        Action::ToggleHitboxes,
+        Action::IncreaseRenderDistance,
+        Action::DecreaseRenderDistance,
+        Action::HotbarSlot1,
+        Action::HotbarSlot2,
+        Action::HotbarSlot3,
+        Action::HotbarSlot4,
+        Action::HotbarSlot5,
+        Action::HotbarSlot6,
+        Action::HotbarSlot7,
+        Action::HotbarSlot8,
+        Action::HotbarSlot9,
+    ];
+
+    /// Select hotbar slots from left to right
+    pub const HOTBAR: [Action; 9] = [
+        Action::HotbarSlot1,
+        Action::HotbarSlot2,
+        Action::HotbarSlot3,
+        Action::HotbarSlot4,
+        Action::HotbarSlot5,
+        Action::HotbarSlot6,
+        Action::HotbarSlot7,
+        Action::HotbarSlot8,
+        Action::HotbarSlot9,
+    ];
+
+    pub const fn default_bindings(&self) -> &'static [Binding] {
+        use Binding::*;
+
+        match self {
+            Action::MoveForward => &[Key(KeyCode::W)],
+            Action::MoveBackward => &[Key(KeyCode::S)],
+            Action::MoveLeft => &[Key(KeyCode::A)],
+            Action::MoveRight => &[Key(KeyCode::D)],
+            Action::Jump => &[Key(KeyCode::Space)],
+            Action::Sneak => &[Key(KeyCode::LeftShift)],
+            Action::Inventory => &[Key(KeyCode::Tab)],
+            Action::Pause => &[Key(KeyCode::Escape)],
+            Action::OpenConsole => &[Key(KeyCode::Slash), Key(KeyCode::GraveAccent)],
+            Action::Break => &[Mouse(MouseButton::Left)],
+            Action::Place => &[Mouse(MouseButton::Right)],
+            Action::PickBlock => &[Mouse(MouseButton::Middle)],
+            Action::ToggleCameraMode => &[Key(KeyCode::F5)],
+            Action::ToggleDebug => &[Key(KeyCode::F3)],
+            Action::ToggleChunkBorders => &[Key(KeyCode::F6)],
+            Action::ToggleHitboxes => &[Key(KeyCode::F7)],
+            Action::IncreaseRenderDistance => &[Key(KeyCode::Equal), Key(KeyCode::KpAdd)],
+            Action::DecreaseRenderDistance => &[Key(KeyCode::Minus), Key(KeyCode::KpSubtract)],
+            Action::HotbarSlot1 => &[Key(KeyCode::Key1)],
+            Action::HotbarSlot2 => &[Key(KeyCode::Key2)],
+            Action::HotbarSlot3 => &[Key(KeyCode::Key3)],
+            Action::HotbarSlot4 => &[Key(KeyCode::Key4)],
+            Action::HotbarSlot5 => &[Key(KeyCode::Key5)],
+            Action::HotbarSlot6 => &[Key(KeyCode::Key6)],
+            Action::HotbarSlot7 => &[Key(KeyCode::Key7)],
+            Action::HotbarSlot8 => &[Key(KeyCode::Key8)],
+            Action::HotbarSlot9 => &[Key(KeyCode::Key9)],
+        }
+    }
+}
+
+impl Display for Action {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl FromStr for Action {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Action::ALL
+            .into_iter()
+            .find(|action| action.to_string() == s)
+            .ok_or_else(|| format!("unknown action `{}`", s))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Binding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+}
+
+impl Binding {
+    pub fn is_down(&self) -> bool {
+        match *self {
+            Binding::Key(key) => is_key_down(key),
+            Binding::Mouse(button) => is_mouse_button_down(button),
+        }
+    }
+
+    pub fn is_pressed(&self) -> bool {
+        match *self {
+            Binding::Key(key) => is_key_pressed(key),
+            Binding::Mouse(button) => is_mouse_button_pressed(button),
+        }
+    }
+}
+
+const MOUSE_BUTTONS: [(&str, MouseButton); 3] = [
+    ("MouseLeft", MouseButton::Left),
+    ("MouseRight", MouseButton::Right),
+    ("MouseMiddle", MouseButton::Middle),
+];
+
+macro_rules! key_codes {
+    ($($key:ident),* $(,)?) => {
+        const KEY_CODES: &[(&str, KeyCode)] = &[$((stringify!($key), KeyCode::$key)),*];
+    };
+}
+
+#[rustfmt::skip]
+key_codes!(
+    Space, Apostrophe, Comma, Minus, Period, Slash,
+    Key0, Key1, Key2, Key3, Key4, Key5, Key6, Key7, Key8, Key9,
+    Semicolon, Equal,
+    A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z,
+    LeftBracket, Backslash, RightBracket, GraveAccent, World1, World2,
+    Escape, Enter, Tab, Backspace, Insert, Delete, Right, Left, Down, Up,
+    PageUp, PageDown, Home, End, CapsLock, ScrollLock, NumLock, PrintScreen, Pause,
+    F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12,
+    Kp0, Kp1, Kp2, Kp3, Kp4, Kp5, Kp6, Kp7, Kp8, Kp9,
+    KpDecimal, KpDivide, KpMultiply, KpSubtract, KpAdd, KpEnter, KpEqual,
+    LeftShift, LeftControl, LeftAlt, LeftSuper,
+    RightShift, RightControl, RightAlt, RightSuper, Menu,
+);
+
+impl Display for Binding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Binding::Key(key) => KEY_CODES.iter().find(|(_, k)| k == key).map(|(name, _)| *name),
+            Binding::Mouse(button) => MOUSE_BUTTONS.iter().find(|(_, b)| b == button).map(|(name, _)| *name),
+        };
+        write!(f, "{}", name.unwrap_or("Unknown"))
+    }
+}
+
+impl FromStr for Binding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some((_, button)) = MOUSE_BUTTONS.iter().find(|(name, _)| *name == s) {
+            return Ok(Binding::Mouse(*button));
+        }
+        if let Some((_, key)) = KEY_CODES.iter().find(|(name, _)| *name == s) {
+            return Ok(Binding::Key(*key));
+        }
+        Err(format!("unknown key or mouse button `{}`", s))
+    }
+}
+
+/// Maps every [`Action`] to the keys and mouse buttons that trigger it.
+///
+/// ```
+/// # use cubic_game::controls::*;
+/// # use macroquad::prelude::KeyCode;
+/// // AZERTY layout
+/// let controls = Controls::from_config("MoveForward = Z, Up\nMoveLeft = Q").unwrap();
+///
+/// assert_eq!(controls.bindings(Action::MoveForward), &[Binding::Key(KeyCode::Z), Binding::Key(KeyCode::Up)]);
+/// assert_eq!(controls.bindings(Action::MoveLeft), &[Binding::Key(KeyCode::Q)]);
+/// assert_eq!(controls.bindings(Action::MoveRight), Action::MoveRight.default_bindings());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Controls {
+    bindings: HashMap<Action, Vec<Binding>>,
+}
+
+impl Default for Controls {
+    fn default() -> Self {
+        Self {
+            bindings: Action::ALL
+                .into_iter()
+                .map(|action| (action, action.default_bindings().to_vec()))
+                .collect(),
+        }
+    }
+}
+
+impl Controls {
+    /// Loads controls from `path`, writing the defaults there if the file doesn't exist yet.
+    /// Invalid lines are reported and the default bindings are kept for them.
+    pub fn load(path: &str) -> Self {
+        let Ok(text) = std::fs::read_to_string(path) else {
+            let controls = Controls::default();
+            if let Err(err) = std::fs::write(path, controls.to_config()) {
+                eprintln!("Unable to write default controls to {}: {}", path, err);
+            }
+            return controls;
+        };
+
+        let (controls, errors) = Controls::parse(&text);
+        for err in &errors {
+            eprintln!("{}: {}", path, err);
+        }
+        controls
+    }
+
+    /// Actions missing from the config keep their default bindings.
+    pub fn from_config(text: &str) -> Result<Self, Vec<ConfigError>> {
+        let (controls, errors) = Controls::parse(text);
+        match errors.is_empty() {
+            true => Ok(controls),
+            false => Err(errors),
+        }
+    }
+
+    fn parse(text: &str) -> (Self, Vec<ConfigError>) {
+        let mut controls = Controls::default();
+
+        let (entries, mut errors) = parse_config(text);
+
+        for ConfigEntry { line, key, value } in entries {
+            let action: Action = match key.parse() {
+                Ok(action) => action,
+                Err(err) => {
+                    errors.push(ConfigError::new(line, err));
+                    continue;
+                }
+            };
+
+            let bindings: Result<Vec<Binding>, String> = value
+                .split(',')
+                .map(str::trim)
+                .filter(|name| !name.is_empty())
+                .map(str::parse)
+                .collect();
+
+            match bindings {
+                Ok(bindings) => controls.set_bindings(action, bindings),
+                Err(err) => errors.push(ConfigError::new(line, err)),
+            }
+        }
+
+        (controls, errors)
+    }
+
+    pub fn to_config(&self) -> String {
+        let mut config = String::from("# Comma-separated key names (W, Space, LeftShift, ...) or MouseLeft, MouseRight, MouseMiddle\n");
+
+        for action in Action::ALL {
+            let bindings: Vec<String> = self.bindings(action).iter().map(Binding::to_string).collect();
+            config += &format!("{} = {}\n", action, bindings.join(", "));
+        }
+        config
+    }
+
+    pub fn bindings(&self, action: Action) -> &[Binding] {
+        self.bindings.get(&action).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    pub fn set_bindings(&mut self, action: Action, bindings: Vec<Binding>) {
+        self.bindings.insert(action, bindings);
+    }
+
+    pub fn is_down(&self, action: Action) -> bool {
+        self.bindings(action).iter().any(Binding::is_down)
+    }
+
+    pub fn is_pressed(&self, action: Action) -> bool {
+        self.bindings(action).iter().any(Binding::is_pressed)
+    }
+}
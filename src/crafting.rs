@@ -0,0 +1,256 @@
+use crate::config::*;
+use crate::item::*;
+
+pub const RECIPES_PATH: &str = "assets/recipes.cfg";
+
+/// Player's own grid in the inventory
+pub const INVENTORY_GRID_SIZE: usize = 2;
+/// Grid of a crafting table
+pub const TABLE_GRID_SIZE: usize = 3;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Recipe {
+    /// Ingredients in rows, `None` is an empty cell
+    Shaped { width: usize, pattern: Vec<Option<Item>>, result: ItemStack },
+    /// One of each ingredient, in any cells
+    Shapeless { ingredients: Vec<Item>, result: ItemStack },
+}
+
+impl Recipe {
+    pub const fn result(&self) -> ItemStack {
+        match self {
+            Recipe::Shaped { result, .. } | Recipe::Shapeless { result, .. } => *result,
+        }
+    }
+
+    /// Shaped recipes match anywhere in the grid and mirrored left to right
+    pub fn matches(&self, grid: &CraftingGrid) -> bool {
+        match self {
+            Recipe::Shaped { width, pattern, .. } => {
+                let Some((grid_width, cells)) = grid.trimmed() else { return false };
+                if grid_width != *width || cells.len() != pattern.len() {
+                    return false;
+                }
+                let mirrored = cells.chunks(grid_width).flat_map(|row| row.iter().rev().copied());
+
+                cells == *pattern || mirrored.eq(pattern.iter().copied())
+            }
+            Recipe::Shapeless { ingredients, .. } => {
+                let mut items: Vec<Item> = grid.slots.iter().flatten().map(|stack| stack.item).collect();
+
+                for ingredient in ingredients {
+                    match items.iter().position(|item| item == ingredient) {
+                        Some(n) => drop(items.swap_remove(n)),
+                        None => return false,
+                    }
+                }
+                items.is_empty()
+            }
+        }
+    }
+}
+
+impl Recipe {
+    /// Ingredients are the value of a recipe line, see [`RECIPES_PATH`] for the format
+    pub fn parse(result: ItemStack, s: &str) -> Result<Self, String> {
+        if let Some(ingredients) = s.strip_prefix("shapeless ") {
+            let ingredients = ingredients.split_whitespace().map(str::parse).collect::<Result<Vec<Item>, _>>()?;
+            return match ingredients.len() {
+                1..=9 => Ok(Recipe::Shapeless { ingredients, result }),
+                n => Err(format!("expected 1..=9 ingredients, got {}", n)),
+            };
+        }
+
+        let Some(rows) = s.strip_prefix("shaped ") else {
+            return Err(format!("expected `shaped` or `shapeless`, got `{}`", s));
+        };
+
+        let rows = rows
+            .split('/')
+            .map(|row| {
+                row.split_whitespace()
+                    .map(|cell| match cell {
+                        "." => Ok(None),
+                        item => item.parse().map(Some),
+                    })
+                    .collect::<Result<Vec<Option<Item>>, _>>()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let width = rows[0].len();
+        if rows.iter().any(|row| row.len() != width) {
+            return Err(String::from("every row of a shaped recipe needs the same number of cells"));
+        }
+        if !(1..=TABLE_GRID_SIZE).contains(&width) || !(1..=TABLE_GRID_SIZE).contains(&rows.len()) {
+            return Err(format!("shaped recipes fit in {0}x{0} cells", TABLE_GRID_SIZE));
+        }
+
+        // Grids are trimmed before matching, so empty rows and columns around the pattern would never match
+        let Some((width, pattern)) = trim(width, &rows.concat()) else {
+            return Err(String::from("shaped recipes need at least one ingredient"));
+        };
+        Ok(Recipe::Shaped { width, pattern, result })
+    }
+}
+
+/// Every recipe of the game, loaded from [`RECIPES_PATH`].
+///
+/// ```
+/// # use cubic_game::crafting::*;
+/// # use cubic_game::item::*;
+/// # use cubic_game::world::*;
+/// let recipes = Recipes::from_config("
+///     Stick 4 = shaped Wheat / Wheat
+///     Torch 4 = shaped Gravel . / . Stick
+///     Seeds 2 = shapeless Wheat Wheat
+/// ").unwrap();
+///
+/// let wheat = Some(ItemStack::new(Item::Wheat, 5));
+/// let gravel = Some(ItemStack::new(Item::Block(BlockType::Gravel), 1));
+/// let stick = Some(ItemStack::new(Item::Stick, 1));
+///
+/// // Anywhere in the grid
+/// let grid = CraftingGrid::from_slots(3, vec![None, None, wheat, None, None, wheat, None, None, None]);
+/// assert_eq!(grid.result(&recipes), Some(ItemStack::new(Item::Stick, 4)));
+///
+/// // Mirrored
+/// let grid = CraftingGrid::from_slots(2, vec![None, gravel, stick, None]);
+/// assert_eq!(grid.result(&recipes).unwrap().item, Item::Block(BlockType::Torch));
+///
+/// // Shapeless in any cells, but nothing extra
+/// let mut grid = CraftingGrid::from_slots(2, vec![wheat, None, None, wheat]);
+/// assert_eq!(grid.craft(&recipes), Some(ItemStack::new(Item::Seeds, 2)));
+/// assert_eq!(grid.slots[0].unwrap().count, 4);
+///
+/// grid.slots[1] = gravel;
+/// assert_eq!(grid.result(&recipes), None);
+///
+/// assert!(Recipes::from_config("Stick = shaped Wheat Wheat / Wheat").is_err());
+/// assert!(Recipes::from_config("Stick = shaped . . / . .").is_err());
+///
+/// // Empty cells around the pattern are trimmed like the grid
+/// let recipes = Recipes::from_config("Stick 4 = shaped . Wheat / . Wheat").unwrap();
+/// let grid = CraftingGrid::from_slots(2, vec![wheat, None, wheat, None]);
+/// assert_eq!(grid.result(&recipes), Some(ItemStack::new(Item::Stick, 4)));
+/// assert!(Recipes::from_config(&std::fs::read_to_string(RECIPES_PATH).unwrap()).is_ok());
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Recipes(pub Vec<Recipe>);
+
+impl Recipes {
+    /// No recipes if the file is missing. Invalid recipes are reported and skipped.
+    pub fn load(path: &str) -> Self {
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(err) => {
+                eprintln!("Unable to load recipes from {}: {}", path, err);
+                return Recipes::default();
+            }
+        };
+
+        let (recipes, errors) = Recipes::parse(&text);
+        for err in &errors {
+            eprintln!("{}: {}", path, err);
+        }
+        recipes
+    }
+
+    pub fn from_config(text: &str) -> Result<Self, Vec<ConfigError>> {
+        let (recipes, errors) = Recipes::parse(text);
+        match errors.is_empty() {
+            true => Ok(recipes),
+            false => Err(errors),
+        }
+    }
+
+    fn parse(text: &str) -> (Self, Vec<ConfigError>) {
+        let (entries, mut errors) = parse_config(text);
+        let mut recipes = Vec::new();
+
+        for ConfigEntry { line, key, value } in entries {
+            let recipe = key.parse().and_then(|result| Recipe::parse(result, value));
+
+            match recipe {
+                Ok(recipe) => recipes.push(recipe),
+                Err(err) => errors.push(ConfigError::new(line, err)),
+            }
+        }
+
+        (Recipes(recipes), errors)
+    }
+
+    /// First recipe matching the grid
+    pub fn find(&self, grid: &CraftingGrid) -> Option<&Recipe> {
+        self.0.iter().find(|recipe| recipe.matches(grid))
+    }
+}
+
+/// Square grid of slots, [`INVENTORY_GRID_SIZE`] or [`TABLE_GRID_SIZE`] wide
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CraftingGrid {
+    pub size: usize,
+    /// In rows
+    pub slots: Vec<Option<ItemStack>>,
+}
+
+impl CraftingGrid {
+    pub fn new(size: usize) -> Self {
+        Self { size, slots: vec![None; size * size] }
+    }
+
+    /// Missing slots are empty and extra ones are dropped
+    pub fn from_slots(size: usize, mut slots: Vec<Option<ItemStack>>) -> Self {
+        slots.resize(size * size, None);
+        Self { size, slots }
+    }
+
+    /// What crafting would make now
+    pub fn result(&self, recipes: &Recipes) -> Option<ItemStack> {
+        recipes.find(self).map(Recipe::result)
+    }
+
+    /// Uses up one item from every filled slot
+    pub fn craft(&mut self, recipes: &Recipes) -> Option<ItemStack> {
+        let result = self.result(recipes)?;
+
+        for slot in &mut self.slots {
+            if let Some(stack) = slot {
+                stack.count -= 1;
+                if stack.count == 0 {
+                    *slot = None;
+                }
+            }
+        }
+        Some(result)
+    }
+
+    /// Takes every stack out of the grid, to return them to the inventory
+    pub fn take_all(&mut self) -> Vec<ItemStack> {
+        self.slots.iter_mut().filter_map(Option::take).collect()
+    }
+
+    /// Width and items of the smallest rectangle around the filled slots
+    fn trimmed(&self) -> Option<(usize, Vec<Option<Item>>)> {
+        let items: Vec<Option<Item>> = self.slots.iter().map(|slot| slot.map(|stack| stack.item)).collect();
+        trim(self.size, &items)
+    }
+}
+
+/// Width and cells of the smallest rectangle around the filled cells of rows `width` cells wide,
+/// `None` if every cell is empty
+fn trim<T: Copy>(width: usize, cells: &[Option<T>]) -> Option<(usize, Vec<Option<T>>)> {
+    let height = cells.len() / width;
+    let filled = |x: usize, y: usize| cells[y * width + x].is_some();
+
+    let columns: Vec<usize> = (0..width).filter(|&x| (0..height).any(|y| filled(x, y))).collect();
+    let rows: Vec<usize> = (0..height).filter(|&y| (0..width).any(|x| filled(x, y))).collect();
+
+    let (left, right) = (*columns.first()?, *columns.last()?);
+    let (top, bottom) = (*rows.first()?, *rows.last()?);
+
+    let trimmed = (top..=bottom)
+        .flat_map(|y| (left..=right).map(move |x| cells[y * width + x]))
+        .collect();
+
+    Some((right - left + 1, trimmed))
+}
@@ -0,0 +1,260 @@
+use std::collections::VecDeque;
+
+use macroquad::prelude::*;
+
+use crate::player::*;
+use crate::ui::Layout;
+use crate::world::entity::*;
+use crate::world::light::Light;
+use crate::world::render::*;
+use crate::world::*;
+
+/// Frames shown in the frame time graph
+pub const FRAME_HISTORY: usize = 120;
+
+/// Height of the graph on the ui canvas for a frame which took this many seconds
+const GRAPH_MAX_FRAME_TIME: f32 = 1.0 / 20.0;
+/// Canvas units
+const FONT_SIZE: f32 = 6.0;
+
+#[rustfmt::skip]
+const BACKGROUND_COLOR: Color = Color { r: 0.0, g: 0.0, b: 0.0, a: 0.5 };
+
+/// Everything shown on the debug screen, gathered once per frame
+#[derive(Debug, Clone, PartialEq)]
+pub struct DebugInfo {
+    pub pos: Vec3,
+    pub front: Vec3,
+    pub target: Option<(BlockPos, BlockState)>,
+    pub biome: Option<Biome>,
+    /// At the eyes of the player
+    pub light: Light,
+    pub loaded_chunks: usize,
+    pub meshes: MeshStats,
+    pub draw: DrawStats,
+    /// Chunks waiting to be meshed in the next frames
+    pub remesh_queue: usize,
+    pub scheduled_ticks: usize,
+    pub entities: usize,
+}
+
+impl DebugInfo {
+    pub fn gather(
+        world: &World,
+        player: &PlayerController,
+        meshes: MeshStats,
+        draw: DrawStats,
+        remesh_queue: usize,
+    ) -> Self {
+        let block = BlockPos::from_vec3(player.pos.0);
+
+        Self {
+            pos: player.pos.0,
+            front: player.front.0,
+            target: raycast(world, player.pos.0, player.front.0, REACH).map(|hit| (hit.pos, world.block(hit.pos).clone())),
+            biome: world.chunk(block.chunk_pos()).map(|chunk| chunk.biome.clone()),
+            light: world.light(block),
+            loaded_chunks: world.n_chunks(),
+            meshes,
+            draw,
+            remesh_queue,
+            scheduled_ticks: world.n_scheduled_ticks(),
+            entities: world.entities().len(),
+        }
+    }
+
+    /// ```
+    /// # use cubic_game::debug::*;
+    /// # use cubic_game::player::*;
+    /// # use cubic_game::world::*;
+    /// # use cubic_game::world::render::*;
+    /// # use macroquad::prelude::*;
+    /// let mut world = World::new();
+    /// world.insert_chunk(ChunkPos::new(-1, 0, 0), Chunk::EMPTY);
+    ///
+    /// let player = PlayerController::new(vec3(-3.5, 10.0, 4.0));
+    /// let info = DebugInfo::gather(&world, &player, MeshStats::default(), DrawStats::default(), 0);
+    /// let lines = info.lines();
+    ///
+    /// assert!(lines.contains(&String::from("XYZ: -3.500 10.000 4.000")));
+    /// assert!(lines.contains(&String::from("Chunk: 12 10 4 in -1 0 0")));
+    /// assert!(lines.contains(&String::from("Biome: Plains")));
+    /// ```
+    pub fn lines(&self) -> Vec<String> {
+        let block = BlockPos::from_vec3(self.pos);
+        let chunk = block.chunk_pos();
+        let (x, y, z) = block.in_chunk();
+
+        let target = match &self.target {
+            Some((pos, state)) => format!("{} at {} {} {}, level {}", state.block_type, pos.x, pos.y, pos.z, state.level),
+            None => String::from("nothing"),
+        };
+        let biome = match &self.biome {
+            Some(biome) => format!("{:?}", biome),
+            None => String::from("not loaded"),
+        };
+
+        vec![
+            format!("XYZ: {:.3} {:.3} {:.3}", self.pos.x, self.pos.y, self.pos.z),
+            format!("Block: {} {} {}", block.x, block.y, block.z),
+            format!("Chunk: {} {} {} in {} {} {}", x, y, z, chunk.x, chunk.y, chunk.z),
+            format!("Facing: {}", facing(self.front)),
+            format!("Looking at: {}", target),
+            format!("Biome: {}", biome),
+            format!("Light: sky {}, block {}", self.light.sky(), self.light.block()),
+            String::new(),
+            format!("Chunks: {} loaded, {} meshed", self.loaded_chunks, self.meshes.chunks),
+            format!("Drawn: {}, culled: {}", self.draw.drawn, self.draw.culled),
+            format!("Meshes: {}, vertices: {}, indices: {}", self.meshes.meshes, self.meshes.vertices, self.meshes.indices),
+            format!("Remesh queue: {}, scheduled ticks: {}", self.remesh_queue, self.scheduled_ticks),
+            format!("Entities: {}", self.entities),
+        ]
+    }
+}
+
+/// Horizontal direction the player is looking in
+fn facing(front: Vec3) -> &'static str {
+    match front.x.abs() > front.z.abs() {
+        true if front.x > 0.0 => "east (+X)",
+        true => "west (-X)",
+        false if front.z > 0.0 => "south (+Z)",
+        false => "north (-Z)",
+    }
+}
+
+/// Toggleable debug screen with a graph of recent frame times.
+///
+/// ```
+/// # use cubic_game::debug::*;
+/// let mut overlay = DebugOverlay::default();
+/// for _ in 0..FRAME_HISTORY + 10 {
+///     overlay.record_frame(0.02);
+/// }
+/// overlay.record_frame(0.05);
+///
+/// assert_eq!(overlay.frame_times.len(), FRAME_HISTORY);
+/// assert_eq!(overlay.max_frame_time(), 0.05);
+/// ```
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct DebugOverlay {
+    pub visible: bool,
+    /// Outlines of the chunks around the player
+    pub chunk_borders: bool,
+    /// Boxes of the player and other entities
+    pub hitboxes: bool,
+    /// In seconds, oldest first
+    pub frame_times: VecDeque<f32>,
+}
+
+impl DebugOverlay {
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    /// Draws the enabled wireframes, has to be called in the 3D pass with the default material
+    pub fn draw_wires(&self, world: &World, player_pos: Vec3, hidden: Option<EntityId>) {
+        if self.chunk_borders {
+            draw_chunk_borders(BlockPos::from_vec3(player_pos).chunk_pos());
+        }
+        if self.hitboxes {
+            draw_hitboxes(world, hidden);
+        }
+    }
+
+    pub fn record_frame(&mut self, frame_time: f32) {
+        if self.frame_times.len() == FRAME_HISTORY {
+            self.frame_times.pop_front();
+        }
+        self.frame_times.push_back(frame_time);
+    }
+
+    pub fn average_frame_time(&self) -> f32 {
+        self.frame_times.iter().sum::<f32>() / self.frame_times.len().max(1) as f32
+    }
+
+    pub fn max_frame_time(&self) -> f32 {
+        self.frame_times.iter().copied().fold(0.0, f32::max)
+    }
+
+    /// Stats in the top left corner and the frame time graph in the bottom left one
+    pub fn draw(&self, info: &DebugInfo) {
+        let scale = Layout::screen().scale;
+        let font_size = FONT_SIZE * scale;
+        let line_height = font_size * 1.1;
+
+        let average = self.average_frame_time();
+        let mut lines = vec![format!(
+            "FPS: {:.0} ({:.1} ms, max {:.1} ms)",
+            1.0 / average.max(f32::EPSILON),
+            average * 1000.0,
+            self.max_frame_time() * 1000.0,
+        )];
+        lines.extend(info.lines());
+
+        for (n, line) in lines.iter().enumerate() {
+            let y = line_height * (n + 1) as f32;
+            if !line.is_empty() {
+                let width = measure_text(line, None, font_size as u16, 1.0).width;
+                draw_rectangle(0.0, y - line_height * 0.8, width + 2.0 * scale, line_height, BACKGROUND_COLOR);
+            }
+            draw_text(line, scale, y, font_size, WHITE);
+        }
+
+        self.draw_frame_graph(scale);
+    }
+
+    /// One bar per frame, green up to 60 FPS, yellow up to 30 FPS and red below
+    fn draw_frame_graph(&self, scale: f32) {
+        let (bar_width, height) = (scale, 40.0 * scale);
+        let bottom = screen_height();
+
+        draw_rectangle(0.0, bottom - height, bar_width * FRAME_HISTORY as f32, height, BACKGROUND_COLOR);
+
+        for (n, frame_time) in self.frame_times.iter().enumerate() {
+            let bar = (frame_time / GRAPH_MAX_FRAME_TIME).min(1.0) * height;
+            let color = match frame_time {
+                t if *t <= 1.0 / 60.0 => GREEN,
+                t if *t <= 1.0 / 30.0 => YELLOW,
+                _ => RED,
+            };
+            draw_rectangle(n as f32 * bar_width, bottom - bar, bar_width, bar, color);
+        }
+
+        let target = bottom - height * (1.0 / 60.0) / GRAPH_MAX_FRAME_TIME;
+        draw_line(0.0, target, bar_width * FRAME_HISTORY as f32, target, scale / 2.0, WHITE);
+    }
+}
+
+/// Chunk of the player in yellow and its horizontal neighbours in blue
+pub fn draw_chunk_borders(center: ChunkPos) {
+    for dx in -1..=1 {
+        for dz in -1..=1 {
+            let pos = ChunkPos::new(center.x + dx, center.y, center.z + dz);
+            let color = match (dx, dz) {
+                (0, 0) => YELLOW,
+                _ => BLUE,
+            };
+            draw_cube_wires(pos.center(), Vec3::splat(CHUNK_SIZE_16 as f32), color);
+        }
+    }
+}
+
+/// Every entity except the `hidden` one, colored by its kind
+pub fn draw_hitboxes(world: &World, hidden: Option<EntityId>) {
+    for (_, entity) in world.entities().iter().filter(|(id, _)| Some(*id) != hidden) {
+        let Aabb { min, max } = entity.aabb();
+        let color = match entity.kind {
+            EntityKind::Player => WHITE,
+            EntityKind::FallingBlock(_) => ORANGE,
+            EntityKind::Item { .. } => GREEN,
+        };
+        draw_cube_wires((min + max) / 2.0, max - min, color);
+
+        // Where the player is looking from its eyes
+        if entity.kind == EntityKind::Player {
+            let eyes = entity.pos + vec3(0.0, entity.kind.eye_height(), 0.0);
+            let front = Front::new(Yaw(entity.yaw), Pitch(entity.pitch));
+            draw_line_3d(eyes, eyes + front.0, BLUE);
+        }
+    }
+}
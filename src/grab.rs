@@ -1,5 +1,5 @@
-use macroquad::prelude::*;
 use derive_more::{Deref, DerefMut};
+use macroquad::prelude::*;
 
 const DEFAULT_GRABBED: bool = true;
 
@@ -28,15 +28,11 @@ impl PartialEq<bool> for Grabbed {
     }
 }
 
-pub fn update_grabbed_state_and_cursor_on_tab_press(grabbed: &mut Grabbed) {
-    if is_key_pressed(KeyCode::Tab) {
+/// Mouse is grabbed for looking around and released while a screen is open
+pub fn update_grabbed_state_and_cursor(grabbed: &mut Grabbed, grab: bool) {
+    if grabbed.0 != grab {
         grabbed.switch();
         set_cursor_grab(grabbed.0);
         show_mouse(!grabbed.0);
     }
 }
-
-pub fn setup_mouse_cursor() {
-    set_cursor_grab(DEFAULT_GRABBED);
-    show_mouse(!DEFAULT_GRABBED);
-}
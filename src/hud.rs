@@ -0,0 +1,57 @@
+use macroquad::prelude::*;
+
+use crate::inventory::*;
+use crate::item::*;
+use crate::ui::Layout;
+use crate::world::render::item_icon;
+
+/// On the ui canvas
+const SLOT_SIZE: f32 = 12.0;
+
+#[rustfmt::skip]
+pub const SLOT_COLOR: Color = Color { r: 0.0, g: 0.0, b: 0.0, a: 0.4 };
+
+/// Hotbar at the bottom of the screen with the selected slot outlined
+pub fn draw_hotbar(inventory: &Inventory, atlas: &Texture2D) {
+    let size = SLOT_SIZE * Layout::screen().scale;
+
+    let left = (screen_width() - size * HOTBAR_SIZE as f32) / 2.0;
+    let top = screen_height() - size - size / 6.0;
+
+    for slot in 0..HOTBAR_SIZE {
+        let x = left + slot as f32 * size;
+
+        draw_rectangle(x, top, size, size, SLOT_COLOR);
+        draw_item_stack(inventory.slots[slot], Rect::new(x, top, size, size), atlas);
+    }
+
+    let selected = left + inventory.selected as f32 * size;
+    draw_rectangle_lines(selected, top, size, size, size / 12.0, WHITE);
+}
+
+/// Icon and count of the stack in the middle of the slot
+pub fn draw_item_stack(stack: Option<ItemStack>, slot: Rect, atlas: &Texture2D) {
+    let Some(stack) = stack else { return };
+
+    let icon_size = slot.w * 2.0 / 3.0;
+    let margin = (slot.w - icon_size) / 2.0;
+
+    if let Some(icon) = item_icon(stack.item) {
+        // Tiles are square and stacked vertically
+        let tile = atlas.width();
+        let source = Rect::new(0.0, icon.up_left().y * atlas.height(), tile, tile);
+
+        draw_texture_ex(atlas, slot.x + margin, slot.y + margin, WHITE, DrawTextureParams {
+            dest_size: Some(vec2(icon_size, icon_size)),
+            source: Some(source),
+            ..Default::default()
+        });
+    }
+
+    if stack.count > 1 {
+        let font_size = slot.h * 0.4;
+        let text = format!("{}", stack.count);
+        let width = measure_text(&text, None, font_size as u16, 1.0).width;
+        draw_text(text.as_str(), slot.right() - width - slot.w / 12.0, slot.bottom() - slot.h / 8.0, font_size, WHITE);
+    }
+}
@@ -0,0 +1,136 @@
+use crate::crafting::*;
+use crate::item::*;
+use crate::world::*;
+
+pub const HOTBAR_SIZE: usize = 9;
+/// Hotbar is the first [`HOTBAR_SIZE`] slots
+pub const INVENTORY_SIZE: usize = 36;
+
+/// Items the player carries, saved with the level.
+///
+/// ```
+/// # use cubic_game::inventory::*;
+/// # use cubic_game::item::*;
+/// # use cubic_game::world::*;
+/// let stone = Item::Block(BlockType::Stone);
+/// let mut inventory = Inventory::default();
+///
+/// assert_eq!(inventory.add(ItemStack::new(stone, 100)), 0);
+/// assert_eq!(inventory.slots[0], Some(ItemStack::new(stone, 64)));
+/// assert_eq!(inventory.slots[1], Some(ItemStack::new(stone, 36)));
+///
+/// inventory.scroll(-1);
+/// assert_eq!(inventory.selected, HOTBAR_SIZE - 1);
+///
+/// inventory.select(1);
+/// assert_eq!(inventory.take_selected(), Some(stone));
+/// assert_eq!(inventory.selected_stack().unwrap().count, 35);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Inventory {
+    pub slots: [Option<ItemStack>; INVENTORY_SIZE],
+    /// Hotbar slot in the player's hand
+    pub selected: usize,
+    /// 2x2 grid, emptied into the slots when the inventory is closed
+    pub crafting: CraftingGrid,
+}
+
+impl Default for Inventory {
+    fn default() -> Self {
+        Self { slots: [None; INVENTORY_SIZE], selected: 0, crafting: CraftingGrid::new(INVENTORY_GRID_SIZE) }
+    }
+}
+
+impl Inventory {
+    /// What a new player starts with
+    pub fn starter() -> Self {
+        let mut inventory = Inventory::default();
+        for item in [
+            Item::Block(BlockType::Stone),
+            Item::Block(BlockType::Dirt),
+            Item::Block(BlockType::Grass),
+            Item::Block(BlockType::Sand),
+            Item::Block(BlockType::Gravel),
+            Item::Block(BlockType::Torch),
+            Item::Seeds,
+            Item::Block(BlockType::Water),
+            Item::Block(BlockType::Lava),
+        ] {
+            inventory.add(ItemStack::new(item, item.max_stack_size()));
+        }
+        inventory
+    }
+
+    pub fn selected_stack(&self) -> Option<&ItemStack> {
+        self.slots[self.selected].as_ref()
+    }
+
+    /// Hotbar slot, out of range slots are ignored
+    pub fn select(&mut self, slot: usize) {
+        if slot < HOTBAR_SIZE {
+            self.selected = slot;
+        }
+    }
+
+    /// Moves the selection by `steps` slots, wrapping around the hotbar
+    pub fn scroll(&mut self, steps: i32) {
+        self.selected = (self.selected as i32 + steps).rem_euclid(HOTBAR_SIZE as i32) as usize;
+    }
+
+    /// Fills stacks of the same item first, then empty slots. Returns how many items didn't fit.
+    pub fn add(&mut self, stack: ItemStack) -> u32 {
+        let ItemStack { item, mut count } = stack;
+
+        for slot in self.slots.iter_mut().flatten().filter(|slot| slot.item == item) {
+            let moved = count.min(item.max_stack_size().saturating_sub(slot.count));
+            slot.count += moved;
+            count -= moved;
+        }
+
+        for slot in self.slots.iter_mut().filter(|slot| slot.is_none()) {
+            if count == 0 {
+                break;
+            }
+            let moved = count.min(item.max_stack_size());
+            *slot = Some(ItemStack::new(item, moved));
+            count -= moved;
+        }
+        count
+    }
+
+    /// Removes one item from the selected slot
+    pub fn take_selected(&mut self) -> Option<Item> {
+        let slot = &mut self.slots[self.selected];
+        let stack = slot.as_mut()?;
+
+        stack.count -= 1;
+        let item = stack.item;
+        if stack.count == 0 {
+            *slot = None;
+        }
+        Some(item)
+    }
+
+    /// Moves the items of the crafting grid back to the slots, returns the stacks which didn't fit
+    pub fn return_crafting_items(&mut self) -> Vec<ItemStack> {
+        let stacks = self.crafting.take_all();
+        stacks
+            .into_iter()
+            .map(|stack| ItemStack::new(stack.item, self.add(stack)))
+            .filter(|stack| stack.count > 0)
+            .collect()
+    }
+
+    pub fn find(&self, item: Item) -> Option<usize> {
+        self.slots.iter().position(|slot| slot.is_some_and(|stack| stack.item == item))
+    }
+
+    /// Selects the item if it is on the hotbar, or swaps it into the selected slot from the rest of the inventory
+    pub fn pick(&mut self, item: Item) {
+        match self.find(item) {
+            Some(slot) if slot < HOTBAR_SIZE => self.selected = slot,
+            Some(slot) => self.slots.swap(slot, self.selected),
+            None => {}
+        }
+    }
+}
@@ -0,0 +1,124 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use crate::world::ticks::MAX_CROP_AGE;
+use crate::world::*;
+
+/// Items which aren't blocks, registered after the block items
+pub const STANDALONE_ITEMS: [Item; 3] = [Item::Stick, Item::Wheat, Item::Seeds];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Item {
+    Block(BlockType),
+    Stick,
+    Wheat,
+    /// Planted as a [`BlockType::Wheat`] crop
+    Seeds,
+}
+
+impl Item {
+    pub const fn max_stack_size(self) -> u32 {
+        match self {
+            Item::Block(BlockType::Water | BlockType::Lava) => 1,
+            _ => 64,
+        }
+    }
+
+    /// Block placed by this item
+    pub const fn block(self) -> Option<BlockState> {
+        match self {
+            Item::Block(BlockType::Air) => None,
+            Item::Block(block_type) => Some(BlockState::new(block_type)),
+            Item::Seeds => Some(BlockState::WHEAT),
+            Item::Stick | Item::Wheat => None,
+        }
+    }
+}
+
+/// Crops are planted from seeds, so they have no block item
+pub const fn has_block_item(block_type: BlockType) -> bool {
+    !matches!(block_type, BlockType::Air | BlockType::Wheat)
+}
+
+/// Every item in the game: block items in the order of [`BlockType::ALL`], then [`STANDALONE_ITEMS`].
+///
+/// ```
+/// # use cubic_game::item::*;
+/// # use cubic_game::world::*;
+/// assert!(registered_items().any(|item| item == Item::Block(BlockType::Stone)));
+/// assert!(registered_items().all(|item| item.to_string().parse() == Ok(item)));
+/// assert_eq!("Seeds".parse(), Ok(Item::Seeds));
+/// ```
+pub fn registered_items() -> impl Iterator<Item = Item> {
+    BlockType::ALL
+        .into_iter()
+        .filter(|block_type| has_block_item(*block_type))
+        .map(Item::Block)
+        .chain(STANDALONE_ITEMS)
+}
+
+impl Display for Item {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Item::Block(block_type) => write!(f, "{}", block_type),
+            item => write!(f, "{:?}", item),
+        }
+    }
+}
+
+impl FromStr for Item {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        registered_items()
+            .find(|item| item.to_string() == s)
+            .ok_or_else(|| format!("unknown item `{}`", s))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ItemStack {
+    pub item: Item,
+    /// 1..=[`Item::max_stack_size`]
+    pub count: u32,
+}
+
+impl ItemStack {
+    pub const fn new(item: Item, count: u32) -> Self {
+        Self { item, count }
+    }
+}
+
+/// `Stone 64`
+impl Display for ItemStack {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.item, self.count)
+    }
+}
+
+impl FromStr for ItemStack {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (item, count) = s.split_once(' ').unwrap_or((s, "1"));
+        let item: Item = item.parse()?;
+
+        match count.trim().parse() {
+            Ok(count) if (1..=item.max_stack_size()).contains(&count) => Ok(ItemStack::new(item, count)),
+            _ => Err(format!("expected 1..={} {}, got `{}`", item.max_stack_size(), item, count)),
+        }
+    }
+}
+
+/// Items a broken block leaves behind
+pub fn block_drops(state: &BlockState) -> Vec<ItemStack> {
+    match state.block_type {
+        BlockType::Air | BlockType::Water | BlockType::Lava => vec![],
+        BlockType::Grass => vec![ItemStack::new(Item::Block(BlockType::Dirt), 1)],
+        BlockType::Wheat if state.level >= MAX_CROP_AGE => {
+            vec![ItemStack::new(Item::Wheat, 1), ItemStack::new(Item::Seeds, 2)]
+        }
+        BlockType::Wheat => vec![ItemStack::new(Item::Seeds, 1)],
+        block_type => vec![ItemStack::new(Item::Block(block_type), 1)],
+    }
+}
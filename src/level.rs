@@ -0,0 +1,170 @@
+use macroquad::prelude::*;
+
+use crate::config::*;
+use crate::inventory::*;
+use crate::player::GameMode;
+use crate::world::generator::*;
+use crate::world::time::WorldTime;
+use crate::world::World;
+
+/// Name of the level file in the directory of a world
+pub const LEVEL_FILE: &str = "level.cfg";
+
+/// World state which isn't stored in chunks, the player's position and inventory, saved to [`LEVEL_FILE`].
+/// Changed chunks are saved next to it with [`crate::saves::Saves::save_chunks`].
+///
+/// ```
+/// # use cubic_game::level::*;
+/// # use cubic_game::inventory::*;
+/// # use cubic_game::world::generator::*;
+/// # use cubic_game::world::time::*;
+/// let generator = Generator { kind: GeneratorKind::Flat, seed: 7 };
+/// # use cubic_game::player::GameMode;
+/// # use macroquad::prelude::*;
+/// let level = Level {
+///     time: WorldTime(12345),
+///     generator,
+///     game_mode: GameMode::Creative,
+///     player_pos: Some(vec3(10.5, 7.25, -3.0)),
+///     inventory: Inventory::starter(),
+/// };
+/// assert_eq!(Level::from_config(&level.to_config()), Ok(level));
+///
+/// assert!(Level::from_config("slot.3 = Stone 65").is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Level {
+    pub time: WorldTime,
+    pub generator: Generator,
+    pub game_mode: GameMode,
+    /// Eyes of the player, `None` to spawn above the origin
+    pub player_pos: Option<Vec3>,
+    pub inventory: Inventory,
+}
+
+impl Default for Level {
+    fn default() -> Self {
+        Self {
+            time: WorldTime::default(),
+            generator: Generator::default(),
+            game_mode: GameMode::default(),
+            player_pos: None,
+            inventory: Inventory::starter(),
+        }
+    }
+}
+
+impl Level {
+    /// New level with the starter inventory
+    pub fn new(generator: Generator) -> Self {
+        Self { generator, ..Level::default() }
+    }
+
+    /// Items left in the crafting grid are saved in the inventory slots, if they fit
+    pub fn from_world(world: &World, generator: Generator, game_mode: GameMode, player_pos: Vec3, inventory: &Inventory) -> Self {
+        let mut inventory = inventory.clone();
+        inventory.return_crafting_items();
+
+        Self { time: world.time(), generator, game_mode, player_pos: Some(player_pos), inventory }
+    }
+
+    pub fn apply(&self, world: &mut World) {
+        world.set_time(self.time);
+    }
+
+    /// New level if the file doesn't exist yet. Invalid lines are reported and replaced with defaults.
+    pub fn load(path: &str) -> Self {
+        let Ok(text) = std::fs::read_to_string(path) else {
+            return Level::default();
+        };
+
+        let (level, errors) = Level::parse(&text);
+        for err in &errors {
+            eprintln!("{}: {}", path, err);
+        }
+        level
+    }
+
+    pub fn save(&self, path: &str) {
+        if let Err(err) = std::fs::write(path, self.to_config()) {
+            eprintln!("Unable to save level to {}: {}", path, err);
+        }
+    }
+
+    pub fn from_config(text: &str) -> Result<Self, Vec<ConfigError>> {
+        let (level, errors) = Level::parse(text);
+        match errors.is_empty() {
+            true => Ok(level),
+            false => Err(errors),
+        }
+    }
+
+    /// Slots missing from the file are empty
+    fn parse(text: &str) -> (Self, Vec<ConfigError>) {
+        let mut level = Level { inventory: Inventory::default(), ..Level::default() };
+
+        let (entries, mut errors) = parse_config(text);
+
+        for ConfigEntry { line, key, value } in entries {
+            let result = match key {
+                "time" => value
+                    .parse()
+                    .map(|time| level.time = WorldTime(time))
+                    .map_err(|_| format!("expected a whole number of ticks, got `{}`", value)),
+                "seed" => value
+                    .parse()
+                    .map(|seed| level.generator.seed = seed)
+                    .map_err(|_| format!("expected a whole number, got `{}`", value)),
+                "generator" => value.parse().map(|kind| level.generator.kind = kind),
+                "game_mode" => value.parse().map(|game_mode| level.game_mode = game_mode),
+                "player_pos" => parse_player_pos(value).map(|pos| level.player_pos = Some(pos)),
+                "selected_slot" => value
+                    .parse()
+                    .ok()
+                    .filter(|slot| *slot < HOTBAR_SIZE)
+                    .map(|slot| level.inventory.selected = slot)
+                    .ok_or_else(|| format!("expected a hotbar slot 0..{}, got `{}`", HOTBAR_SIZE, value)),
+                key if key.starts_with("slot.") => match key["slot.".len()..].parse::<usize>() {
+                    Ok(slot) if slot < INVENTORY_SIZE => value.parse().map(|stack| level.inventory.slots[slot] = Some(stack)),
+                    _ => Err(format!("expected an inventory slot 0..{}, got `{}`", INVENTORY_SIZE, key)),
+                },
+                _ => Err(format!("unknown level value `{}`", key)),
+            };
+
+            if let Err(err) = result {
+                errors.push(ConfigError::new(line, err));
+            }
+        }
+
+        (level, errors)
+    }
+
+    pub fn to_config(&self) -> String {
+        let mut config = format!("time = {}\n", self.time.0);
+
+        config.push_str(&format!("seed = {}\n", self.generator.seed));
+        config.push_str(&format!("generator = {}\n", self.generator.kind));
+        config.push_str(&format!("game_mode = {}\n", self.game_mode));
+        if let Some(pos) = self.player_pos {
+            config.push_str(&format!("player_pos = {} {} {}\n", pos.x, pos.y, pos.z));
+        }
+
+        config.push_str(&format!("selected_slot = {}\n", self.inventory.selected));
+        for (n, slot) in self.inventory.slots.iter().enumerate() {
+            if let Some(stack) = slot {
+                config.push_str(&format!("slot.{} = {}\n", n, stack));
+            }
+        }
+        config
+    }
+}
+
+/// `x y z`, finite numbers only
+fn parse_player_pos(value: &str) -> Result<Vec3, String> {
+    let coords: Result<Vec<f32>, _> = value.split_whitespace().map(str::parse).collect();
+
+    match coords.as_deref() {
+        Ok([x, y, z]) if [x, y, z].iter().all(|n| n.is_finite()) => Ok(vec3(*x, *y, *z)),
+        _ => Err(format!("expected a position `x y z`, got `{}`", value)),
+    }
+}
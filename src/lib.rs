@@ -1,136 +1,377 @@
-#![feature(const_fn_floating_point_arithmetic)]
-
 use macroquad::prelude::*;
 
-mod grab;
+pub mod commands;
+use commands::*;
+
+pub mod config;
+
+pub mod console;
+use console::*;
+
+pub mod crafting;
+use crafting::*;
+
+pub mod debug;
+use debug::*;
+
+pub mod controls;
+use controls::*;
+
+pub mod grab;
 use grab::*;
 
-mod player;
+pub mod hud;
+use hud::*;
+
+pub mod inventory;
+use inventory::*;
+
+pub mod item;
+use item::*;
+
+pub mod level;
+use level::*;
+
+pub mod player;
 use player::*;
 
-mod world;
+pub mod saves;
+use saves::*;
+
+pub mod schematic;
+use schematic::*;
+
+pub mod settings;
+use settings::*;
+
+pub mod tick;
+use tick::*;
+
+pub mod ui;
+use ui::*;
+
+pub mod world;
+use world::entity::*;
+use world::generator::*;
 use world::render::*;
+use world::time::*;
 use world::*;
 
-#[rustfmt::skip]
-const SKY_COLOR: Color = Color { r: 0.3, g: 0.3, b: 0.5, a: 1.0 };
+/// Everything which outlives a single world
+pub struct Client {
+    pub atlas: Texture2D,
+    pub controls: Controls,
+    pub settings: Settings,
+    pub recipes: Recipes,
+    pub saves: Saves,
+    pub schematics: Schematics,
+    pub chunk_material: ChunkMaterial,
+    pub sky: Sky,
+    pub grabbed: Grabbed,
+}
 
-#[rustfmt::skip]
-pub async fn run_client() {
+pub async fn run_client(settings: Settings) {
+    let mut client = Client::load(settings).await;
 
-    let atlas: Texture2D = load_texture("assets/atlas.png").await.unwrap();
-    atlas.set_filter(FilterMode::Nearest);
+    while let Some(name) = select_world(&mut client).await {
+        play_world(&mut client, &name).await;
+    }
+}
 
-    let mut yaw = Yaw::default();
-    let mut pitch = Pitch::default();
+impl Client {
+    /// `settings` are loaded by the caller, because the window is configured with them before the client exists
+    pub async fn load(settings: Settings) -> Self {
+        let atlas: Texture2D = load_texture("assets/atlas.png").await.unwrap();
+        atlas.set_filter(FilterMode::Nearest);
 
-    let mut front = Front::new(yaw, pitch);
-    let mut right = Right::new(front);
-    let mut up = Up::new(right, front);
+        Self {
+            atlas,
+            controls: Controls::load(CONTROLS_PATH),
+            settings,
+            recipes: Recipes::load(RECIPES_PATH),
+            saves: Saves::new(SAVES_DIR),
+            schematics: Schematics::new(SCHEMATICS_DIR),
+            chunk_material: ChunkMaterial::load().await.unwrap_or_else(|err| panic!("{}", err)),
+            sky: Sky::new(),
+            grabbed: Grabbed::default(),
+        }
+    }
+}
+
+/// Title screen with the saved worlds. Returns the name of the world to play, `None` to quit the game
+#[rustfmt::skip]
+pub async fn select_world(client: &mut Client) -> Option<String> {
+    let Client { atlas, controls, settings, recipes, saves, grabbed, .. } = client;
 
-    let mut player_pos = PlayerPos::default();
+    let mut ui = Ui::default();
+    ui.open(Screen::title(saves.list()), settings);
 
-    let mut last_mouse_pos: LastMousePos = mouse_position().into();
-    let mut current_mouse_pos: CurrentMousePos = mouse_position().into();
+    update_grabbed_state_and_cursor(grabbed, false);
 
-    let mut grabbed = Grabbed::default();
+    loop {
+        let event = match controls.is_pressed(Action::Pause) {
+            true if !matches!(ui.screen, Some(Screen::Title { .. })) => Some(UiEvent::ShowWorlds),
+            _ => ui.update(&UiInput::read(), &mut Inventory::default(), recipes, settings),
+        };
 
-    let mut chunk_models: Vec<(ChunkPos, ChunkModel)> = Vec::with_capacity(100);
+        let result = match event {
+            Some(UiEvent::Play(name)) => return Some(name),
+            Some(UiEvent::Quit) => return None,
+            Some(UiEvent::SettingsChanged) | Some(UiEvent::Close) | None => Ok(false),
+            Some(UiEvent::ShowWorlds) => Ok(true),
+            Some(UiEvent::CreateWorld { name, seed, generator }) => {
+                let seed = seed_from_text(&seed).unwrap_or_else(|| miniquad::date::now().to_bits());
+                saves.create(&name, &Level::new(Generator { kind: generator, seed })).map(|_| true)
+            }
+            Some(UiEvent::RenameWorld { from, to }) => saves.rename(&from, &to).map(|_| true),
+            Some(UiEvent::DeleteWorld(name)) => saves.delete(&name).map(|_| true),
+        };
 
-    for x in -20..20 {
-        for z in -20..20 {
-            chunk_models.push((ChunkPos::new(x, 0, z), make_model(UvTexture::SAND)));
+        match result {
+            Ok(true) => ui.open(Screen::title(saves.list()), settings),
+            Ok(false) => {}
+            Err(err) => ui.message = Some(err),
         }
+
+        clear_background(Sky::horizon_color(WorldTime(NOON)));
+        ui.draw(&Inventory::default(), recipes, atlas);
+
+        next_frame().await
     }
+}
+
+/// Runs the world until the player saves and quits to the title screen
+#[rustfmt::skip]
+pub async fn play_world(client: &mut Client, name: &str) {
+    let Client { atlas, controls, settings, recipes, saves, schematics, chunk_material, sky, grabbed } = client;
 
-    let chunk_meshes: Vec<_> = build_chunk_meshes(chunk_models, Some(atlas.clone())).collect();
+    let level = saves.load(name);
+    let generator = level.generator;
+
+    let mut world = World::new();
+    level.apply(&mut world);
+    let mut inventory = level.inventory;
+    let mut game_mode = level.game_mode;
+    saves.load_chunks(name, &mut world);
+    generator.generate_missing_chunks(&mut world, settings.render_distance);
+
+    let spawn_height = generator.height(0, 0) as f32 + 2.6;
+    let mut player = PlayerController::new(level.player_pos.unwrap_or(vec3(0.5, spawn_height, 0.5)));
+
+    player.last_mouse_pos = mouse_position().into();
+    player.current_mouse_pos = mouse_position().into();
+
+    let player_id = world.entities_mut().spawn(Entity::new(EntityKind::Player, player.pos.0));
+
+    let mut ui = Ui::default();
+    let mut tick_clock = TickClock::default();
+    let mut chunk_meshes = ChunkMeshes::new(Some(atlas.clone()));
+    let mut debug_overlay = DebugOverlay::default();
+    let mut console = Console::default();
+
+    update_grabbed_state_and_cursor(grabbed, true);
 
-    setup_mouse_cursor();
-    
     loop {
-        if is_key_pressed(KeyCode::Escape) { break; }
+        if !ui.is_paused() {
+            for _ in 0..tick_clock.advance(get_frame_time()) {
+                world.tick();
+            }
+        }
+
+        // Escape closing the console shouldn't open the pause menu as well
+        let typing = console.open;
+
+        if console.open {
+            if let Some(command) = update_console_on_press(&mut console) {
+                let mut ctx = CommandContext { world: &mut world, player: &mut player, inventory: &mut inventory, generator, game_mode: &mut game_mode, schematics };
+                console.print(format!("> {}", command));
+                console.print(run_command(&mut ctx, &command).unwrap_or_else(|err| err));
+            }
+        } else if !ui.is_open() && controls.is_pressed(Action::OpenConsole) {
+            console.open();
+            // The key which opened the console isn't part of the command
+            while get_char_pressed().is_some() {}
+        }
 
-        update_grabbed_state_and_cursor_on_tab_press(&mut grabbed);
+        let event = match typing || console.open {
+            true => None,
+            false => update_ui_on_press(&mut ui, settings, controls)
+                .or_else(|| ui.update(&UiInput::read(), &mut inventory, recipes, settings)),
+        };
 
-        *current_mouse_pos = mouse_position().into();
+        match event {
+            Some(UiEvent::Close) => drop_items(&mut world, player.pos.0, ui.close(&mut inventory)),
+            Some(UiEvent::SettingsChanged) => {
+                settings.save(SETTINGS_PATH);
+                generator.generate_missing_chunks(&mut world, settings.render_distance);
+            }
+            Some(UiEvent::Quit) => {
+                drop_items(&mut world, player.pos.0, ui.close(&mut inventory));
+                saves.save(name, &Level::from_world(&world, generator, game_mode, player.pos.0, &inventory));
+                if let Err(err) = saves.save_chunks(name, &world, generator) {
+                    eprintln!("{}", err);
+                }
+                return;
+            }
+            // Title screen events
+            Some(_) | None => {}
+        }
+
+        update_grabbed_state_and_cursor(grabbed, !ui.is_open() && !console.open);
+
+        // Minus and equals are typed into the console
+        if !console.open && update_render_distance_on_press(settings, controls) {
+            settings.save(SETTINGS_PATH);
+            generator.generate_missing_chunks(&mut world, settings.render_distance);
+        }
+
+        if controls.is_pressed(Action::ToggleDebug) {
+            debug_overlay.toggle();
+        }
+        if controls.is_pressed(Action::ToggleChunkBorders) {
+            debug_overlay.chunk_borders = !debug_overlay.chunk_borders;
+        }
+        if controls.is_pressed(Action::ToggleHitboxes) {
+            debug_overlay.hitboxes = !debug_overlay.hitboxes;
+        }
+        debug_overlay.record_frame(get_frame_time());
+
+        chunk_meshes.update(&mut world, BlockPos::from_vec3(player.pos.0).chunk_pos(), settings.lod_distance());
+        let remesh_queue = chunk_meshes.queued();
+
+        player.current_mouse_pos = mouse_position().into();
+
+        if grabbed.0 {
+            player.apply_input(&PlayerInput::read(controls, player.last_mouse_pos, player.current_mouse_pos), settings);
+            if update_world_after_break_or_place_press(&mut world, &mut inventory, player.pos, player.front, game_mode, controls) {
+                ui.open(Screen::crafting_table(), settings);
+            }
+            update_hotbar_on_press(&mut inventory, controls);
 
-        if *grabbed {
-            update_yaw_pitch_after_mouse_pos_changed(&current_mouse_pos, &last_mouse_pos, &mut yaw, &mut pitch);
-            update_player_pos_after_front_right_up_changed(&mut player_pos, front, right);
-            update_front_right_up_vecs_after_yaw_pitch_changed(&yaw, &pitch, &mut front, &mut right, &mut up);
+            if controls.is_pressed(Action::ToggleCameraMode) {
+                player.cycle_camera_mode();
+            }
         }
 
-        clear_background(SKY_COLOR);
+        if game_mode != GameMode::Spectator {
+            pick_up_items(&mut world, player_id, &mut inventory);
+        }
+
+        if let Some(entity) = world.entities_mut().get_mut(player_id) {
+            entity.pos = player.pos.0 - vec3(0.0, entity.kind.eye_height(), 0.0);
+            (entity.yaw, entity.pitch) = (*player.yaw, *player.pitch);
+        }
+
+        let time = world.time();
+        let horizon_color = Sky::horizon_color(time);
+
+        clear_background(horizon_color);
+
+        let camera = player.camera(&world, settings);
 
-        set_camera(&Camera3D {
-            position: *player_pos,
-            up: *up,
-            target: *player_pos + *front,
-            ..Default::default()
+        set_camera(&camera);
+
+        sky.draw(camera.position, time);
+
+        chunk_material.apply(&ChunkUniforms {
+            camera_pos: camera.position,
+            sun_direction: time.sun_direction(),
+            sky_tint: time.sky_tint(),
+            fog_color: horizon_color,
+            render_distance: (settings.render_distance as usize * CHUNK_SIZE_16) as f32,
         });
 
-        for chunk_mesh in &chunk_meshes {
-            draw_mesh(&chunk_mesh);
+        // Seen from its own eyes the player would only block the view
+        let hidden = (player.mode == CameraMode::FirstPerson).then_some(player_id);
+
+        for mesh in build_entities_mesh(&world, hidden, Some(atlas.clone())) {
+            draw_mesh(&mesh);
         }
 
+        let draw_stats = chunk_meshes.draw(&camera, settings.render_distance, chunk_material);
+
+        gl_use_default_material();
+
+        debug_overlay.draw_wires(&world, player.pos.0, hidden);
+
         /* Back to screen space */ set_default_camera();
 
-        print_n_meshes(&chunk_meshes);
-        render_text_overlay(player_pos, get_fps());
+        if debug_overlay.visible {
+            debug_overlay.draw(&DebugInfo::gather(&world, &player, chunk_meshes.stats(), draw_stats, remesh_queue));
+        }
+        draw_hotbar(&inventory, atlas);
+        ui.draw(&inventory, recipes, atlas);
+        console.draw();
 
-        last_mouse_pos.0 = mouse_position().into();
+        player.last_mouse_pos = mouse_position().into();
 
         next_frame().await
     }
 }
 
-fn make_model(tex: UvTexture) -> ChunkModel {
-    let mut model = ChunkModel::EMPTY;
-    for x in 0..16 {
-        for z in 0..16 {
-            model.set(x, 3, z, BlockModel::Top(tex))
-        }
+/// Returns `true` if render distance was changed
+fn update_render_distance_on_press(settings: &mut Settings, controls: &Controls) -> bool {
+    let render_distance = settings.render_distance;
+
+    if controls.is_pressed(Action::IncreaseRenderDistance) {
+        settings.set_render_distance(render_distance + 1);
+    }
+    if controls.is_pressed(Action::DecreaseRenderDistance) {
+        settings.set_render_distance(render_distance.saturating_sub(1));
     }
-    model
+
+    settings.render_distance != render_distance
 }
 
-fn print_n_meshes(chunk_meshes: &Vec<Mesh>) {
-    let y = 40.0 + 40.0 * 2.0;
-    for (n, mesh) in chunk_meshes.into_iter().enumerate() {
-        draw_text(
-            format!("Mesh#{}, IND ({})", n, mesh.indices.len()).as_str(),
-            10.0,
-            y + 40.0 * n as f32,
-            20.0,
-            BLACK,
-        );
-        draw_text(
-            format!("VERT ({})", mesh.vertices.len()).as_str(),
-            10.0,
-            20.0 + y + 40.0 * n as f32,
-            20.0,
-            BLACK,
-        );
+/// Tab opens the inventory and Escape the pause menu, either of them closes the open screen
+fn update_ui_on_press(ui: &mut Ui, settings: &Settings, controls: &Controls) -> Option<UiEvent> {
+    let inventory = controls.is_pressed(Action::Inventory);
+    let pause = controls.is_pressed(Action::Pause);
+
+    if ui.is_open() && (inventory || pause) {
+        return Some(UiEvent::Close);
+    }
+    if inventory {
+        ui.open(Screen::Inventory, settings);
+    }
+    if pause {
+        ui.open(Screen::Pause, settings);
     }
+    None
 }
 
-fn render_text_overlay(player_pos: PlayerPos, fps: i32) {
-    draw_text(
-        format!(
-            "X: {:.2} Y: {:.2} Z: {:.2}",
-            player_pos.x, player_pos.y, player_pos.z
-        )
-        .as_str(),
-        10.0,
-        40.0,
-        60.0,
-        BLACK,
-    );
-    draw_text(
-        format!("FPS: {}", fps).as_str(),
-        10.0,
-        40.0 + 40.0,
-        60.0,
-        BLACK,
-    );
+/// Typing into the open console, returns the submitted command
+fn update_console_on_press(console: &mut Console) -> Option<String> {
+    let typed: String = std::iter::from_fn(get_char_pressed).filter(|char| !char.is_control()).collect();
+    if !typed.is_empty() {
+        console.type_text(&typed);
+    }
+
+    if is_key_pressed(KeyCode::Backspace) {
+        console.backspace();
+    }
+    if is_key_pressed(KeyCode::Up) {
+        console.history_up();
+    }
+    if is_key_pressed(KeyCode::Down) {
+        console.history_down();
+    }
+    if is_key_pressed(KeyCode::Tab) {
+        console.complete();
+    }
+    if is_key_pressed(KeyCode::Escape) {
+        console.close();
+    }
+
+    match is_key_pressed(KeyCode::Enter) || is_key_pressed(KeyCode::KpEnter) {
+        true => console.submit(),
+        false => None,
+    }
+}
+
+/// Items which didn't fit back into the inventory fall at the feet of the player
+fn drop_items(world: &mut World, pos: Vec3, stacks: Vec<ItemStack>) {
+    for stack in stacks {
+        world.spawn_item(pos, stack);
+    }
 }
@@ -1,10 +1,18 @@
+use std::sync::OnceLock;
+
+use cubic_game::settings::*;
 use macroquad::prelude::*;
 
+/// Settings are loaded once in `conf`, before the window exists, and handed to the client from there
+static SETTINGS: OnceLock<Settings> = OnceLock::new();
+
 fn conf() -> Conf {
+    let settings = SETTINGS.get_or_init(|| Settings::load(SETTINGS_PATH));
+
     Conf {
         window_title: String::from("CubicGame"),
         platform: miniquad::conf::Platform {
-            // swap_interval: Some(0),
+            swap_interval: Some(settings.swap_interval()),
             ..Default::default()
         },
         ..Default::default()
@@ -13,5 +21,6 @@ fn conf() -> Conf {
 
 #[macroquad::main(conf)]
 async fn main() {
-    cubic_game::run_client().await;
+    let settings = SETTINGS.get_or_init(|| Settings::load(SETTINGS_PATH)).clone();
+    cubic_game::run_client(settings).await;
 }
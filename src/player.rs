@@ -1,15 +1,236 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
 use derive_more::{Deref, DerefMut};
 use macroquad::prelude::*;
 
-const LOOK_SPEED: f32 = 0.1;
-const MOVE_SPEED: f32 = 0.2;
+use crate::controls::*;
+use crate::inventory::*;
+use crate::item::*;
+use crate::settings::*;
+use crate::world::entity::*;
+use crate::world::item_entity::PICKUP_DELAY;
+use crate::world::*;
 
 const UP: Vec3 = vec3(0.0, 1.0, 0.0);
 
+/// How far away blocks can be broken and placed
+pub const REACH: f32 = 5.0;
+
+/// How far behind the player the third-person camera is, unless a block is in the way
+const THIRD_PERSON_DISTANCE: f32 = 4.0;
+
+const MAX_PITCH: f32 = 1.5;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum CameraMode {
+    #[default]
+    FirstPerson,
+    /// Behind the player, looking the same way
+    ThirdPerson,
+    /// Camera moves on its own, the player stays where it was
+    FreeCam,
+}
+
+impl CameraMode {
+    pub const fn next(self) -> Self {
+        match self {
+            CameraMode::FirstPerson => CameraMode::ThirdPerson,
+            CameraMode::ThirdPerson => CameraMode::FreeCam,
+            CameraMode::FreeCam => CameraMode::FirstPerson,
+        }
+    }
+}
+
+/// What the player is allowed to do with blocks and items
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum GameMode {
+    /// Placing blocks uses up items and broken blocks drop them
+    #[default]
+    Survival,
+    /// Items are never used up and broken blocks drop nothing
+    Creative,
+    /// Looks around without touching the world
+    Spectator,
+}
+
+impl GameMode {
+    pub const ALL: [GameMode; 3] = [GameMode::Survival, GameMode::Creative, GameMode::Spectator];
+}
+
+impl Display for GameMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// Case-insensitive, `creative` works as well as `Creative`
+impl FromStr for GameMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        GameMode::ALL
+            .into_iter()
+            .find(|mode| mode.to_string().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("unknown game mode `{}`", s))
+    }
+}
+
+/// Everything the player controller needs from one frame of input
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct PlayerInput {
+    /// In pixels
+    pub mouse_delta: Vec2,
+    /// -1.0..=1.0 along right, up and forward
+    pub movement: Vec3,
+    pub frame_time: f32,
+}
+
+impl PlayerInput {
+    pub fn read(controls: &Controls, last_mouse_pos: LastMousePos, current_mouse_pos: CurrentMousePos) -> Self {
+        let axis = |positive: Action, negative: Action| {
+            controls.is_down(positive) as i32 as f32 - controls.is_down(negative) as i32 as f32
+        };
+
+        Self {
+            mouse_delta: current_mouse_pos.0 - last_mouse_pos.0,
+            movement: vec3(
+                axis(Action::MoveRight, Action::MoveLeft),
+                axis(Action::Jump, Action::Sneak),
+                axis(Action::MoveForward, Action::MoveBackward),
+            ),
+            frame_time: get_frame_time(),
+        }
+    }
+}
+
+/// Position and orientation of the player and the camera looking through its eyes, or following it.
+///
 /// ```
+/// # use cubic_game::player::*;
+/// # use cubic_game::settings::*;
+/// # use cubic_game::world::*;
+/// # use macroquad::prelude::*;
+/// let world = World::new();
+/// let settings = Settings::default();
+///
+/// let mut player = PlayerController::new(vec3(0.0, 10.0, 0.0));
+/// let input = PlayerInput { movement: vec3(0.0, 0.0, 1.0), ..Default::default() };
+///
+/// player.apply_input(&input, &settings);
+/// assert!((player.pos.0 - vec3(0.0, 10.0, 0.0)).dot(player.front.0) > 0.0);
+///
+/// player.cycle_camera_mode();
+/// assert_eq!(player.mode, CameraMode::ThirdPerson);
+/// assert!(player.camera(&world, &settings).position.distance(player.pos.0) > 1.0);
+///
+/// // Free camera leaves the player behind
+/// player.cycle_camera_mode();
+/// let pos = player.pos;
+/// player.apply_input(&input, &settings);
+/// assert_eq!(player.pos.0, pos.0);
+/// assert_ne!(player.camera(&world, &settings).position, pos.0);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct PlayerController {
+    /// Eyes of the player
+    pub pos: PlayerPos,
+    pub yaw: Yaw,
+    pub pitch: Pitch,
+    pub front: Front,
+    pub right: Right,
+    pub up: Up,
+    pub last_mouse_pos: LastMousePos,
+    pub current_mouse_pos: CurrentMousePos,
+    pub mode: CameraMode,
+    /// Where the camera is in [`CameraMode::FreeCam`]
+    pub free_cam_pos: Vec3,
+}
+
+impl PlayerController {
+    pub fn new(pos: Vec3) -> Self {
+        let (yaw, pitch) = (Yaw::default(), Pitch::default());
+        let front = Front::new(yaw, pitch);
+        let right = Right::new(front);
+
+        Self {
+            pos: PlayerPos(pos),
+            yaw,
+            pitch,
+            front,
+            right,
+            up: Up::new(right, front),
+            last_mouse_pos: LastMousePos(Vec2::ZERO),
+            current_mouse_pos: CurrentMousePos(Vec2::ZERO),
+            mode: CameraMode::default(),
+            free_cam_pos: pos,
+        }
+    }
+
+    /// Turns with the mouse and moves the player, or the camera in [`CameraMode::FreeCam`]
+    pub fn apply_input(&mut self, input: &PlayerInput, settings: &Settings) {
+        self.yaw.0 += input.mouse_delta.x * input.frame_time * settings.sensitivity;
+        self.pitch.0 -= input.mouse_delta.y * input.frame_time * settings.sensitivity;
+        self.pitch.0 = self.pitch.0.clamp(-MAX_PITCH, MAX_PITCH);
+
+        self.front.update(self.yaw, self.pitch);
+        self.right.update(self.front);
+        self.up.update(self.right, self.front);
+
+        let velocity = zero_y_normalize(self.front.0) * input.movement.z
+            + zero_y_normalize(self.right.0) * input.movement.x
+            + UP * input.movement.y;
+
+        if velocity != Vec3::ZERO {
+            let step = velocity.normalize() * settings.move_speed;
+            match self.mode {
+                CameraMode::FreeCam => self.free_cam_pos += step,
+                _ => self.pos.0 += step,
+            }
+        }
+    }
+
+    /// Free camera starts at the eyes of the player
+    pub fn cycle_camera_mode(&mut self) {
+        self.mode = self.mode.next();
+        if self.mode == CameraMode::FreeCam {
+            self.free_cam_pos = self.pos.0;
+        }
+    }
+
+    /// Third-person camera is pulled in front of blocks behind the player
+    pub fn camera(&self, world: &World, settings: &Settings) -> Camera3D {
+        let position = match self.mode {
+            CameraMode::FirstPerson => self.pos.0,
+            CameraMode::ThirdPerson => {
+                let distance = match raycast(world, self.pos.0, -self.front.0, THIRD_PERSON_DISTANCE) {
+                    Some(hit) => {
+                        let center = vec3(hit.pos.x as f32, hit.pos.y as f32, hit.pos.z as f32) + 0.5;
+                        (center.distance(self.pos.0) - 0.8).max(0.0)
+                    }
+                    None => THIRD_PERSON_DISTANCE,
+                };
+                self.pos.0 - self.front.0 * distance
+            }
+            CameraMode::FreeCam => self.free_cam_pos,
+        };
+
+        Camera3D {
+            position,
+            up: self.up.0,
+            target: position + self.front.0,
+            fovy: settings.fov.to_radians(),
+            ..Default::default()
+        }
+    }
+}
+
+/// ```no_run
+/// # use cubic_game::player::*;
+/// # use macroquad::prelude::*;
 /// let mp: LastMousePos = mouse_position().into();
 /// ```
-#[derive(Deref, DerefMut, Clone, Copy)]
+#[derive(Debug, Deref, DerefMut, Clone, Copy)]
 pub struct LastMousePos(pub Vec2);
 
 impl From<(f32, f32)> for LastMousePos {
@@ -24,7 +245,7 @@ impl From<Vec2> for LastMousePos {
     }
 }
 
-#[derive(Deref, DerefMut, Clone, Copy)]
+#[derive(Debug, Deref, DerefMut, Clone, Copy)]
 pub struct CurrentMousePos(pub Vec2);
 
 impl From<(f32, f32)> for CurrentMousePos {
@@ -39,55 +260,93 @@ impl From<Vec2> for CurrentMousePos {
     }
 }
 
-pub fn update_yaw_pitch_after_mouse_pos_changed(
-    current_mouse_pos: &CurrentMousePos,
-    last_mouse_pos: &LastMousePos,
-
-    yaw: &mut Yaw,
-    pitch: &mut Pitch,
-) {
-    let mouse_delta: Vec2 = current_mouse_pos.0 - last_mouse_pos.0;
-    let frame_time = get_frame_time();
-
-    yaw.0 += mouse_delta.x * frame_time * LOOK_SPEED;
-    pitch.0 += mouse_delta.y * frame_time * -LOOK_SPEED;
-
-    pitch.0 = if pitch.0 > 1.5 { 1.5 } else { pitch.0 };
-    pitch.0 = if pitch.0 < -1.5 { -1.5 } else { pitch.0 };
-}
-
-#[derive(Deref, DerefMut, Clone, Copy, Default)]
+#[derive(Debug, Deref, DerefMut, Clone, Copy, Default)]
 pub struct PlayerPos(pub Vec3);
 
-pub fn update_player_pos_after_front_right_up_changed(
-    player_pos: &mut PlayerPos,
+/// Breaks the block the player is looking at, places the selected block against it or picks it from the inventory.
+/// Returns `true` if Place was pressed on a crafting table, which opens it instead
+pub fn update_world_after_break_or_place_press(
+    world: &mut World,
+    inventory: &mut Inventory,
 
+    player_pos: PlayerPos,
     front: Front,
-    right: Right,
-) {
-    let mut velocity: Vec3 = Vec3::ZERO;
 
-    if is_key_down(KeyCode::W) {
-        velocity += zero_y_normalize(front.0);
+    game_mode: GameMode,
+    controls: &Controls,
+) -> bool {
+    if game_mode == GameMode::Spectator {
+        return false;
     }
-    if is_key_down(KeyCode::S) {
-        velocity -= zero_y_normalize(front.0);
+    let Some(hit) = raycast(world, player_pos.0, front.0, REACH) else { return false };
+
+    if controls.is_pressed(Action::Break) {
+        match game_mode {
+            GameMode::Creative => _ = world.set_block(hit.pos, BlockState::AIR),
+            _ => world.break_block(hit.pos),
+        }
     }
-    if is_key_down(KeyCode::A) {
-        velocity -= zero_y_normalize(right.0);
+    if controls.is_pressed(Action::Place) {
+        if world.block(hit.pos).block_type == BlockType::CraftingTable {
+            return true;
+        }
+
+        let selected = inventory.selected_stack().and_then(|stack| stack.item.block());
+
+        if let (Some(face), Some(block)) = (hit.face, selected) {
+            let pos = hit.pos.neighbour(face);
+            if world.block(pos).is_empty() && !world.is_blocked_by_entity(pos, &block) && world.set_block(pos, block) && game_mode == GameMode::Survival {
+                inventory.take_selected();
+            }
+        }
     }
-    if is_key_down(KeyCode::D) {
-        velocity += zero_y_normalize(right.0);
+    if controls.is_pressed(Action::PickBlock) {
+        let state = world.block(hit.pos);
+        let item = match state.block_type {
+            BlockType::Wheat => Item::Seeds,
+            block_type => Item::Block(block_type),
+        };
+        inventory.pick(item);
     }
-    if is_key_down(KeyCode::Space) {
-        velocity += UP;
+    false
+}
+
+/// Dropped items the player walks into go to the inventory, whatever doesn't fit stays on the ground
+pub fn pick_up_items(world: &mut World, player_id: EntityId, inventory: &mut Inventory) {
+    let Some(player) = world.entities().get(player_id) else { return };
+    let reach = player.aabb();
+    let reach = Aabb { min: reach.min - 0.5, max: reach.max + 0.5 };
+
+    let touching: Vec<EntityId> = world
+        .entities()
+        .iter()
+        .filter(|(_, entity)| matches!(entity.kind, EntityKind::Item { age, .. } if age >= PICKUP_DELAY))
+        .filter(|(_, entity)| entity.aabb().intersects(&reach))
+        .map(|(id, _)| id)
+        .collect();
+
+    for id in touching {
+        let Some(entity) = world.entities_mut().get_mut(id) else { continue };
+        let EntityKind::Item { stack, .. } = &mut entity.kind else { continue };
+
+        stack.count = inventory.add(*stack);
+        if stack.count == 0 {
+            world.entities_mut().remove(id);
+        }
     }
-    if is_key_down(KeyCode::LeftShift) {
-        velocity -= UP;
+}
+
+/// Number keys select a hotbar slot and the mouse wheel scrolls through them
+pub fn update_hotbar_on_press(inventory: &mut Inventory, controls: &Controls) {
+    for (slot, action) in Action::HOTBAR.into_iter().enumerate() {
+        if controls.is_pressed(action) {
+            inventory.select(slot);
+        }
     }
 
-    if velocity != Vec3::ZERO {
-        player_pos.0 += velocity.normalize() * MOVE_SPEED;
+    let (_, wheel) = mouse_wheel();
+    if wheel != 0.0 {
+        inventory.scroll(-wheel.signum() as i32);
     }
 }
 
@@ -96,27 +355,6 @@ fn zero_y_normalize(mut vec: Vec3) -> Vec3 {
     vec.normalize()
 }
 
-pub fn update_front_right_up_vecs_after_yaw_pitch_changed(
-    yaw: &Yaw,
-    pitch: &Pitch,
-
-    front: &mut Front,
-    right: &mut Right,
-    up: &mut Up,
-) {
-    front.0 = vec3(
-        yaw.cos() * pitch.cos(),
-        pitch.sin(),
-        yaw.sin() * pitch.cos(),
-    )
-    .normalize();
-
-    right.0 = front.cross(UP).normalize();
-
-    up.0 = right.cross(front.0).normalize();
-}
-
-
 #[derive(Debug, Deref, DerefMut, Clone, Copy)]
 pub struct Front(pub Vec3);
 
@@ -164,6 +402,7 @@ impl Up {
 }
 
 /// ```
+/// # use cubic_game::player::*;
 /// let yaw = Yaw::default();
 /// ```
 #[derive(Debug, Deref, DerefMut, Clone, Copy)]
@@ -176,6 +415,7 @@ impl Default for Yaw {
 }
 
 /// ```
+/// # use cubic_game::player::*;
 /// let pitch = Pitch::default();
 /// ```
 #[derive(Debug, Deref, DerefMut, Clone, Copy)]
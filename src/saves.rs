@@ -0,0 +1,202 @@
+use std::path::PathBuf;
+
+use crate::config::ConfigError;
+use crate::level::*;
+use crate::schematic::*;
+use crate::world::generator::*;
+use crate::world::*;
+
+pub const SAVES_DIR: &str = "saves";
+
+/// Directory in the directory of a world with one file per changed chunk
+pub const CHUNKS_DIR: &str = "chunks";
+
+pub const MAX_WORLD_NAME_LEN: usize = 32;
+
+/// Directory with one subdirectory per world, named after it.
+///
+/// ```
+/// # use cubic_game::level::*;
+/// # use cubic_game::saves::*;
+/// let dir = std::env::temp_dir().join(format!("cubic-saves-{}", std::process::id()));
+/// let saves = Saves::new(dir.clone());
+///
+/// saves.create("Valley", &Level::default()).unwrap();
+/// saves.create("Island", &Level::default()).unwrap();
+/// assert!(saves.create("Valley", &Level::default()).is_err());
+/// assert!(saves.create("../Valley", &Level::default()).is_err());
+/// assert_eq!(saves.list(), ["Island", "Valley"]);
+///
+/// saves.rename("Island", "Atoll").unwrap();
+/// saves.delete("Valley").unwrap();
+/// assert_eq!(saves.list(), ["Atoll"]);
+/// assert_eq!(saves.load("Atoll"), Level::default());
+/// # std::fs::remove_dir_all(dir).unwrap();
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Saves {
+    pub dir: PathBuf,
+}
+
+impl Saves {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Names of the saved worlds in alphabetical order
+    pub fn list(&self) -> Vec<String> {
+        let Ok(entries) = std::fs::read_dir(&self.dir) else { return Vec::new() };
+
+        let mut names: Vec<String> = entries
+            .flatten()
+            .filter(|entry| entry.path().join(LEVEL_FILE).is_file())
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .collect();
+        names.sort();
+        names
+    }
+
+    pub fn exists(&self, name: &str) -> bool {
+        self.level_path(name).is_file()
+    }
+
+    pub fn level_path(&self, name: &str) -> PathBuf {
+        self.dir.join(name).join(LEVEL_FILE)
+    }
+
+    /// New level if the world has no level file
+    pub fn load(&self, name: &str) -> Level {
+        Level::load(&self.level_path(name).to_string_lossy())
+    }
+
+    pub fn save(&self, name: &str, level: &Level) {
+        level.save(&self.level_path(name).to_string_lossy());
+    }
+
+    /// Chunk files are named after the chunk position, `x.y.z.cfg`
+    pub fn chunk_path(&self, name: &str, pos: ChunkPos) -> PathBuf {
+        self.dir.join(name).join(CHUNKS_DIR).join(format!("{}.{}.{}.cfg", pos.x, pos.y, pos.z))
+    }
+
+    /// Saves loaded chunks which differ from what the generator makes, in the schematic format.
+    /// Files of chunks which are back to their generated terrain are removed.
+    ///
+    /// ```
+    /// # use cubic_game::level::*;
+    /// # use cubic_game::saves::*;
+    /// # use cubic_game::world::*;
+    /// # use cubic_game::world::generator::*;
+    /// let dir = std::env::temp_dir().join(format!("cubic-chunks-{}", std::process::id()));
+    /// let saves = Saves::new(dir.clone());
+    /// let generator = Generator { kind: GeneratorKind::Flat, seed: 1 };
+    /// saves.create("Quarry", &Level::new(generator)).unwrap();
+    ///
+    /// let mut world = World::new();
+    /// generator.generate_missing_chunks(&mut world, 1);
+    /// world.set_block(BlockPos::new(-5, 3, 7), BlockState::TORCH);
+    /// saves.save_chunks("Quarry", &world, generator).unwrap();
+    ///
+    /// let mut loaded = World::new();
+    /// saves.load_chunks("Quarry", &mut loaded);
+    /// assert_eq!(loaded.n_chunks(), 1);
+    /// assert_eq!(loaded.block(BlockPos::new(-5, 3, 7)), &BlockState::TORCH);
+    ///
+    /// world.set_block(BlockPos::new(-5, 3, 7), BlockState::GRASS);
+    /// saves.save_chunks("Quarry", &world, generator).unwrap();
+    /// assert_eq!(std::fs::read_dir(dir.join("Quarry").join(CHUNKS_DIR)).unwrap().count(), 0);
+    /// # std::fs::remove_dir_all(dir).unwrap();
+    /// ```
+    pub fn save_chunks(&self, name: &str, world: &World, generator: Generator) -> Result<(), String> {
+        let dir = self.dir.join(name).join(CHUNKS_DIR);
+        std::fs::create_dir_all(&dir).map_err(|err| format!("Unable to create {}: {}", dir.display(), err))?;
+
+        for (pos, chunk) in world.chunks() {
+            let path = self.chunk_path(name, pos);
+
+            if chunk.blocks == generator.chunk(pos).blocks {
+                if path.exists() {
+                    std::fs::remove_file(&path).map_err(|err| format!("Unable to remove {}: {}", path.display(), err))?;
+                }
+                continue;
+            }
+
+            let region = BlockRegion::with_size(pos.into(), (CHUNK_SIZE_16, CHUNK_SIZE_16, CHUNK_SIZE_16));
+            std::fs::write(&path, Schematic::from_world(world, region).to_config())
+                .map_err(|err| format!("Unable to save chunk to {}: {}", path.display(), err))?;
+        }
+        Ok(())
+    }
+
+    /// Inserts every saved chunk into the world, before the generator fills in the rest.
+    /// Broken files are reported and left to the generator.
+    pub fn load_chunks(&self, name: &str, world: &mut World) {
+        let Ok(entries) = std::fs::read_dir(self.dir.join(name).join(CHUNKS_DIR)) else { return };
+
+        for path in entries.flatten().map(|entry| entry.path()) {
+            match load_chunk(&path.to_string_lossy()) {
+                Ok((pos, chunk)) => world.insert_chunk(pos, chunk),
+                Err(err) => eprintln!("{}: {}", path.display(), err),
+            }
+        }
+    }
+
+    pub fn create(&self, name: &str, level: &Level) -> Result<(), String> {
+        check_world_name(name)?;
+        if self.dir.join(name).exists() {
+            return Err(format!("World `{}` already exists", name));
+        }
+
+        std::fs::create_dir_all(self.dir.join(name)).map_err(|err| format!("Unable to create world `{}`: {}", name, err))?;
+        std::fs::write(self.level_path(name), level.to_config())
+            .map_err(|err| format!("Unable to save world `{}`: {}", name, err))
+    }
+
+    pub fn rename(&self, from: &str, to: &str) -> Result<(), String> {
+        check_world_name(to)?;
+        if self.dir.join(to).exists() {
+            return Err(format!("World `{}` already exists", to));
+        }
+
+        std::fs::rename(self.dir.join(from), self.dir.join(to))
+            .map_err(|err| format!("Unable to rename world `{}`: {}", from, err))
+    }
+
+    pub fn delete(&self, name: &str) -> Result<(), String> {
+        check_world_name(name)?;
+        std::fs::remove_dir_all(self.dir.join(name)).map_err(|err| format!("Unable to delete world `{}`: {}", name, err))
+    }
+}
+
+/// Names become directory names, so they can't leave the saves directory
+pub fn check_world_name(name: &str) -> Result<(), String> {
+    if name.trim().is_empty() {
+        return Err(String::from("World name can't be empty"));
+    }
+    if name.chars().count() > MAX_WORLD_NAME_LEN {
+        return Err(format!("World name can't be longer than {} characters", MAX_WORLD_NAME_LEN));
+    }
+    if name.starts_with('.') || name != name.trim() || name.contains(['/', '\\', ':']) {
+        return Err(format!("`{}` can't be used as a world name", name));
+    }
+    Ok(())
+}
+
+/// Position comes from the file name, see [`Saves::chunk_path`]
+fn load_chunk(path: &str) -> Result<(ChunkPos, Chunk), String> {
+    let file_name = std::path::Path::new(path).file_stem().and_then(|stem| stem.to_str()).unwrap_or_default();
+    let coords: Vec<isize> = file_name.split('.').filter_map(|n| n.parse().ok()).collect();
+    let [x, y, z] = coords.as_slice() else {
+        return Err(format!("expected a file named `x.y.z.cfg` after the chunk position, got `{}`", file_name));
+    };
+
+    let text = std::fs::read_to_string(path).map_err(|err| err.to_string())?;
+    let schematic = Schematic::from_config(&text).map_err(|errors| {
+        let errors: Vec<String> = errors.iter().map(ConfigError::to_string).collect();
+        errors.join(", ")
+    })?;
+
+    if schematic.size != (CHUNK_SIZE_16, CHUNK_SIZE_16, CHUNK_SIZE_16) {
+        return Err(format!("expected {} blocks along every side", CHUNK_SIZE_16));
+    }
+    Ok((ChunkPos::new(*x, *y, *z), Chunk::from_fn(|x, y, z| schematic.get(x, y, z).clone())))
+}
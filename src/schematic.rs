@@ -0,0 +1,388 @@
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use crate::config::*;
+use crate::world::*;
+
+/// Schematics are shared between worlds, so they live next to the saves directory
+pub const SCHEMATICS_DIR: &str = "schematics";
+
+pub const SCHEMATIC_EXTENSION: &str = "schematic";
+
+/// Most blocks a schematic file may contain
+pub const MAX_SCHEMATIC_VOLUME: usize = 256 * 256 * 256;
+
+/// Rotation around the vertical axis, clockwise when looking down
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Rotation {
+    #[default]
+    Deg0,
+    Deg90,
+    Deg180,
+    Deg270,
+}
+
+impl Rotation {
+    pub const ALL: [Rotation; 4] = [Rotation::Deg0, Rotation::Deg90, Rotation::Deg180, Rotation::Deg270];
+}
+
+impl Display for Rotation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let degrees = match self {
+            Rotation::Deg0 => 0,
+            Rotation::Deg90 => 90,
+            Rotation::Deg180 => 180,
+            Rotation::Deg270 => 270,
+        };
+        write!(f, "{}", degrees)
+    }
+}
+
+impl FromStr for Rotation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Rotation::ALL
+            .into_iter()
+            .find(|rotation| rotation.to_string() == s)
+            .ok_or_else(|| format!("expected a rotation of 0, 90, 180 or 270 degrees, got `{}`", s))
+    }
+}
+
+/// Axis along which the blocks are flipped, before rotating them
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Mirror {
+    #[default]
+    None,
+    X,
+    Z,
+}
+
+impl Mirror {
+    pub const ALL: [Mirror; 3] = [Mirror::None, Mirror::X, Mirror::Z];
+}
+
+impl Display for Mirror {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&format!("{:?}", self).to_lowercase())
+    }
+}
+
+impl FromStr for Mirror {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Mirror::ALL
+            .into_iter()
+            .find(|mirror| mirror.to_string() == s.to_lowercase())
+            .ok_or_else(|| format!("expected a mirror of none, x or z, got `{}`", s))
+    }
+}
+
+/// Blocks of a region which can be saved to a file and pasted into any world.
+///
+/// ```
+/// # use cubic_game::schematic::*;
+/// # use cubic_game::world::*;
+/// let mut world = World::new();
+/// world.insert_chunk(ChunkPos::new(0, 0, 0), Chunk::EMPTY);
+///
+/// // L-shaped wall, 3 blocks along x and 2 along z
+/// world.set_block(BlockPos::new(0, 0, 0), BlockState::STONE);
+/// world.set_block(BlockPos::new(1, 0, 0), BlockState::STONE);
+/// world.set_block(BlockPos::new(2, 0, 0), BlockState::STONE);
+/// world.set_block(BlockPos::new(2, 0, 1), BlockState::with_level(BlockType::Water, 3));
+///
+/// let schematic = Schematic::from_world(&world, BlockRegion::new(BlockPos::new(0, 0, 0), BlockPos::new(2, 0, 1)));
+/// assert_eq!(schematic.palette, [BlockState::STONE, BlockState::AIR, BlockState::with_level(BlockType::Water, 3)]);
+/// assert_eq!(Schematic::from_config(&schematic.to_config()), Ok(schematic.clone()));
+///
+/// let rotated = schematic.transformed(Rotation::Deg90, Mirror::None);
+/// assert_eq!(rotated.size, (2, 1, 3));
+/// assert_eq!(rotated.get(0, 0, 2), &BlockState::with_level(BlockType::Water, 3));
+///
+/// let mirrored = schematic.transformed(Rotation::Deg0, Mirror::X);
+/// assert_eq!(mirrored.get(0, 0, 1), &BlockState::with_level(BlockType::Water, 3));
+///
+/// assert_eq!(rotated.paste(&mut world, BlockPos::new(8, 4, 8)), 4);
+/// assert_eq!(world.block(BlockPos::new(9, 4, 10)), &BlockState::STONE);
+///
+/// assert!(Schematic::from_config("size = 2 1 1\npalette.0 = Stone\nblocks = 0*3").is_err());
+/// assert!(Schematic::from_config("size = 2 1 1\npalette.0 = Stone\nblocks = 1 18446744073709551615*0").is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Schematic {
+    /// Blocks along x, y and z
+    pub size: (usize, usize, usize),
+    /// Every different block state in the schematic
+    pub palette: Vec<BlockState>,
+    /// Palette index of every block, in the order of [`BlockRegion::positions`]
+    pub blocks: Vec<usize>,
+}
+
+impl Schematic {
+    /// Unloaded blocks are saved as air. The region has to fit in memory, check its [`BlockRegion::volume`] first
+    pub fn from_world(world: &World, region: BlockRegion) -> Self {
+        let size = region.size().expect("region is too big for a schematic");
+        let mut palette: Vec<BlockState> = Vec::new();
+        let mut blocks = Vec::new();
+
+        for pos in region.positions() {
+            let state = world.block(pos);
+            let index = match palette.iter().position(|known| known == state) {
+                Some(index) => index,
+                None => {
+                    palette.push(state.clone());
+                    palette.len() - 1
+                }
+            };
+            blocks.push(index);
+        }
+
+        Self { size, palette, blocks }
+    }
+
+    pub fn volume(&self) -> usize {
+        self.blocks.len()
+    }
+
+    pub fn get(&self, x: usize, y: usize, z: usize) -> &BlockState {
+        &self.palette[self.blocks[index(self.size, x, y, z)]]
+    }
+
+    /// Mirrored first, then rotated
+    pub fn transformed(&self, rotation: Rotation, mirror: Mirror) -> Schematic {
+        let (sx, sy, sz) = self.size;
+        let size = match rotation {
+            Rotation::Deg0 | Rotation::Deg180 => self.size,
+            Rotation::Deg90 | Rotation::Deg270 => (sz, sy, sx),
+        };
+
+        let mut blocks = vec![0; self.blocks.len()];
+        for x in 0..sx {
+            for y in 0..sy {
+                for z in 0..sz {
+                    let (mx, mz) = match mirror {
+                        Mirror::None => (x, z),
+                        Mirror::X => (sx - 1 - x, z),
+                        Mirror::Z => (x, sz - 1 - z),
+                    };
+                    let (nx, nz) = match rotation {
+                        Rotation::Deg0 => (mx, mz),
+                        Rotation::Deg90 => (sz - 1 - mz, mx),
+                        Rotation::Deg180 => (sx - 1 - mx, sz - 1 - mz),
+                        Rotation::Deg270 => (mz, sx - 1 - mx),
+                    };
+                    blocks[index(size, nx, y, nz)] = self.blocks[index(self.size, x, y, z)];
+                }
+            }
+        }
+
+        Schematic { size, palette: self.palette.clone(), blocks }
+    }
+
+    /// Places the schematic with its lowest corner at `at`, air included. Returns the number of changed blocks
+    pub fn paste(&self, world: &mut World, at: BlockPos) -> usize {
+        let region = BlockRegion::with_size(at, self.size);
+        world.set_blocks(region.positions().zip(&self.blocks).map(|(pos, index)| (pos, self.palette[*index].clone())))
+    }
+
+    pub fn from_config(text: &str) -> Result<Self, Vec<ConfigError>> {
+        let (entries, mut errors) = parse_config(text);
+        let end = text.lines().count();
+
+        let mut size = None;
+        let mut palette = BTreeMap::new();
+        let mut blocks = None;
+
+        for ConfigEntry { line, key, value } in entries {
+            let result = match key {
+                "size" => parse_size(value).map(|parsed| size = Some(parsed)),
+                "blocks" => parse_blocks(value).map(|parsed| blocks = Some((line, parsed))),
+                key if key.starts_with("palette.") => match key["palette.".len()..].parse::<usize>() {
+                    Ok(index) => parse_block_state(value).map(|state| {
+                        palette.insert(index, state);
+                    }),
+                    Err(_) => Err(format!("expected a palette index, got `{}`", key)),
+                },
+                _ => Err(format!("unknown schematic value `{}`", key)),
+            };
+
+            if let Err(err) = result {
+                errors.push(ConfigError::new(line, err));
+            }
+        }
+
+        let (Some(size), Some((line, blocks))) = (size, blocks) else {
+            errors.push(ConfigError::new(end, "schematic needs both `size` and `blocks`"));
+            return Err(errors);
+        };
+
+        if palette.keys().enumerate().any(|(n, index)| n != *index) {
+            errors.push(ConfigError::new(line, "palette indices have to count up from 0"));
+        }
+        let palette: Vec<BlockState> = palette.into_values().collect();
+        let volume = size.0 * size.1 * size.2;
+
+        if blocks.len() != volume {
+            errors.push(ConfigError::new(line, format!("expected {} blocks, got {}", volume, blocks.len())));
+        }
+        if blocks.iter().any(|index| *index >= palette.len()) {
+            errors.push(ConfigError::new(line, format!("palette has {} blocks, indices are out of range", palette.len())));
+        }
+
+        match errors.is_empty() {
+            true => Ok(Self { size, palette, blocks }),
+            false => Err(errors),
+        }
+    }
+
+    /// Block indices are run-length encoded, `3*1` stands for `1 1 1`
+    pub fn to_config(&self) -> String {
+        let (x, y, z) = self.size;
+        let mut config = format!("size = {} {} {}\n", x, y, z);
+
+        for (n, state) in self.palette.iter().enumerate() {
+            match state.level {
+                0 => config.push_str(&format!("palette.{} = {}\n", n, state.block_type)),
+                level => config.push_str(&format!("palette.{} = {} {}\n", n, state.block_type, level)),
+            }
+        }
+
+        let mut runs: Vec<(usize, usize)> = Vec::new();
+        for index in &self.blocks {
+            match runs.last_mut() {
+                Some((last, count)) if last == index => *count += 1,
+                _ => runs.push((*index, 1)),
+            }
+        }
+        let runs: Vec<String> = runs
+            .into_iter()
+            .map(|(index, count)| match count {
+                1 => index.to_string(),
+                count => format!("{}*{}", count, index),
+            })
+            .collect();
+
+        config.push_str(&format!("blocks = {}\n", runs.join(" ")));
+        config
+    }
+}
+
+/// Position of a block in [`Schematic::blocks`]
+fn index((_, sy, sz): (usize, usize, usize), x: usize, y: usize, z: usize) -> usize {
+    (x * sy + y) * sz + z
+}
+
+fn parse_size(value: &str) -> Result<(usize, usize, usize), String> {
+    let sizes: Vec<usize> = value.split_whitespace().map(|n| n.parse().unwrap_or(0)).collect();
+
+    match sizes.as_slice() {
+        [x, y, z] if *x > 0 && *y > 0 && *z > 0 && x.saturating_mul(*y).saturating_mul(*z) <= MAX_SCHEMATIC_VOLUME => {
+            Ok((*x, *y, *z))
+        }
+        _ => Err(format!("expected 3 sizes above 0 with at most {} blocks, got `{}`", MAX_SCHEMATIC_VOLUME, value)),
+    }
+}
+
+/// `Stone` or `Water 3`
+fn parse_block_state(value: &str) -> Result<BlockState, String> {
+    match value.split_whitespace().collect::<Vec<_>>().as_slice() {
+        [block_type] => Ok(BlockState::new(block_type.parse()?)),
+        [block_type, level] => {
+            let level = level.parse().map_err(|_| format!("expected a block level, got `{}`", level))?;
+            Ok(BlockState::with_level(block_type.parse()?, level))
+        }
+        _ => Err(format!("expected a block and an optional level, got `{}`", value)),
+    }
+}
+
+fn parse_blocks(value: &str) -> Result<Vec<usize>, String> {
+    let mut blocks = Vec::new();
+
+    for run in value.split_whitespace() {
+        let (count, index) = run.split_once('*').unwrap_or(("1", run));
+        let (Ok(count), Ok(index)) = (count.parse::<usize>(), index.parse::<usize>()) else {
+            return Err(format!("expected a palette index or `count*index`, got `{}`", run));
+        };
+        if blocks.len().checked_add(count).is_none_or(|total| total > MAX_SCHEMATIC_VOLUME) {
+            return Err(format!("schematic can't have more than {} blocks", MAX_SCHEMATIC_VOLUME));
+        }
+        blocks.extend(std::iter::repeat_n(index, count));
+    }
+    Ok(blocks)
+}
+
+/// Directory with one file per schematic, named after it.
+///
+/// ```
+/// # use cubic_game::schematic::*;
+/// # use cubic_game::world::*;
+/// let dir = std::env::temp_dir().join(format!("cubic-schematics-{}", std::process::id()));
+/// let schematics = Schematics::new(dir.clone());
+///
+/// let schematic = Schematic::from_world(&World::new(), BlockRegion::new(BlockPos::new(0, 0, 0), BlockPos::new(1, 2, 3)));
+/// schematics.save("hut", &schematic).unwrap();
+/// assert!(schematics.save("../hut", &schematic).is_err());
+///
+/// assert_eq!(schematics.list(), ["hut"]);
+/// assert_eq!(schematics.load("hut"), Ok(schematic));
+/// assert!(schematics.load("tower").is_err());
+/// # std::fs::remove_dir_all(dir).unwrap();
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schematics {
+    pub dir: PathBuf,
+}
+
+impl Schematics {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Names of the saved schematics in alphabetical order
+    pub fn list(&self) -> Vec<String> {
+        let Ok(entries) = std::fs::read_dir(&self.dir) else { return Vec::new() };
+
+        let mut names: Vec<String> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|extension| extension == SCHEMATIC_EXTENSION))
+            .filter_map(|path| Some(path.file_stem()?.to_str()?.to_string()))
+            .collect();
+        names.sort();
+        names
+    }
+
+    pub fn path(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{}.{}", name, SCHEMATIC_EXTENSION))
+    }
+
+    pub fn load(&self, name: &str) -> Result<Schematic, String> {
+        check_schematic_name(name)?;
+        let text = std::fs::read_to_string(self.path(name)).map_err(|err| format!("Unable to load schematic `{}`: {}", name, err))?;
+
+        Schematic::from_config(&text).map_err(|errors| {
+            let errors: Vec<String> = errors.iter().map(ConfigError::to_string).collect();
+            format!("Schematic `{}` is broken: {}", name, errors.join(", "))
+        })
+    }
+
+    /// Replaces the schematic if it already exists
+    pub fn save(&self, name: &str, schematic: &Schematic) -> Result<(), String> {
+        check_schematic_name(name)?;
+        std::fs::create_dir_all(&self.dir).map_err(|err| format!("Unable to create {}: {}", self.dir.display(), err))?;
+        std::fs::write(self.path(name), schematic.to_config())
+            .map_err(|err| format!("Unable to save schematic `{}`: {}", name, err))
+    }
+}
+
+/// Names become file names, so only letters, digits, `-` and `_` are allowed
+pub fn check_schematic_name(name: &str) -> Result<(), String> {
+    match !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_') {
+        true => Ok(()),
+        false => Err(format!("`{}` can't be used as a schematic name", name)),
+    }
+}
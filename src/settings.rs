@@ -0,0 +1,169 @@
+use std::ops::RangeInclusive;
+
+use crate::config::*;
+
+pub const SETTINGS_PATH: &str = "settings.cfg";
+
+pub const FOV_RANGE: RangeInclusive<f32> = 30.0..=110.0;
+pub const SENSITIVITY_RANGE: RangeInclusive<f32> = 0.01..=1.0;
+pub const MOVE_SPEED_RANGE: RangeInclusive<f32> = 0.05..=2.0;
+pub const RENDER_DISTANCE_RANGE: RangeInclusive<u32> = 2..=32;
+pub const LOD_DISTANCE_RANGE: RangeInclusive<u32> = 1..=32;
+
+/// Client settings, stored in [`SETTINGS_PATH`].
+///
+/// ```
+/// # use cubic_game::settings::*;
+/// let settings = Settings::from_config("fov = 70\nrender_distance = 100").unwrap();
+///
+/// assert_eq!(settings.fov, 70.0);
+/// assert_eq!(settings.render_distance, *RENDER_DISTANCE_RANGE.end());
+/// assert_eq!(settings.vsync, Settings::default().vsync);
+///
+/// assert!(Settings::from_config("fov = wide").is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Settings {
+    /// Vertical field of view in degrees
+    pub fov: f32,
+    pub sensitivity: f32,
+    pub move_speed: f32,
+    /// In chunks
+    pub render_distance: u32,
+    /// Draw distant chunks with less detail
+    pub lod: bool,
+    /// Width of every level of detail ring in chunks
+    pub lod_distance: u32,
+    /// Only applied on startup, when the window is created, so a change needs a restart of the game
+    pub vsync: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            fov: 45.0,
+            sensitivity: 0.1,
+            move_speed: 0.2,
+            render_distance: 20,
+            lod: true,
+            lod_distance: 6,
+            vsync: true,
+        }
+    }
+}
+
+impl Settings {
+    /// Loads settings from `path`, writing the defaults there if the file doesn't exist yet.
+    /// Invalid lines are reported and replaced with defaults.
+    pub fn load(path: &str) -> Self {
+        let Ok(text) = std::fs::read_to_string(path) else {
+            let settings = Settings::default();
+            settings.save(path);
+            return settings;
+        };
+
+        let (settings, errors) = Settings::parse(&text);
+        for err in &errors {
+            eprintln!("{}: {}", path, err);
+        }
+        settings
+    }
+
+    pub fn save(&self, path: &str) {
+        if let Err(err) = std::fs::write(path, self.to_config()) {
+            eprintln!("Unable to save settings to {}: {}", path, err);
+        }
+    }
+
+    /// Values out of range are clamped, missing values are left at their defaults.
+    pub fn from_config(text: &str) -> Result<Self, Vec<ConfigError>> {
+        let (settings, errors) = Settings::parse(text);
+        match errors.is_empty() {
+            true => Ok(settings),
+            false => Err(errors),
+        }
+    }
+
+    fn parse(text: &str) -> (Self, Vec<ConfigError>) {
+        let mut settings = Settings::default();
+
+        let (entries, mut errors) = parse_config(text);
+
+        for ConfigEntry { line, key, value } in entries {
+            let result = match key {
+                "fov" => parse_f32(value).map(|fov| settings.set_fov(fov)),
+                "sensitivity" => parse_f32(value).map(|s| settings.set_sensitivity(s)),
+                "move_speed" => parse_f32(value).map(|speed| settings.set_move_speed(speed)),
+                "render_distance" => value
+                    .parse()
+                    .map(|distance| settings.set_render_distance(distance))
+                    .map_err(|_| format!("expected a whole number of chunks, got `{}`", value)),
+                "lod" => value
+                    .parse()
+                    .map(|lod| settings.lod = lod)
+                    .map_err(|_| format!("expected `true` or `false`, got `{}`", value)),
+                "lod_distance" => value
+                    .parse()
+                    .map(|distance| settings.set_lod_distance(distance))
+                    .map_err(|_| format!("expected a whole number of chunks, got `{}`", value)),
+                "vsync" => value
+                    .parse()
+                    .map(|vsync| settings.vsync = vsync)
+                    .map_err(|_| format!("expected `true` or `false`, got `{}`", value)),
+                _ => Err(format!("unknown setting `{}`", key)),
+            };
+
+            if let Err(err) = result {
+                errors.push(ConfigError::new(line, err));
+            }
+        }
+
+        (settings, errors)
+    }
+
+    pub fn to_config(&self) -> String {
+        format!(
+            "fov = {}\nsensitivity = {}\nmove_speed = {}\nrender_distance = {}\nlod = {}\nlod_distance = {}\nvsync = {}\n",
+            self.fov, self.sensitivity, self.move_speed, self.render_distance, self.lod, self.lod_distance, self.vsync,
+        )
+    }
+
+    pub fn set_fov(&mut self, fov: f32) {
+        self.fov = fov.clamp(*FOV_RANGE.start(), *FOV_RANGE.end());
+    }
+
+    pub fn set_sensitivity(&mut self, sensitivity: f32) {
+        self.sensitivity = sensitivity.clamp(*SENSITIVITY_RANGE.start(), *SENSITIVITY_RANGE.end());
+    }
+
+    pub fn set_move_speed(&mut self, move_speed: f32) {
+        self.move_speed = move_speed.clamp(*MOVE_SPEED_RANGE.start(), *MOVE_SPEED_RANGE.end());
+    }
+
+    pub fn set_render_distance(&mut self, render_distance: u32) {
+        self.render_distance = render_distance.clamp(*RENDER_DISTANCE_RANGE.start(), *RENDER_DISTANCE_RANGE.end());
+    }
+
+    pub fn set_lod_distance(&mut self, lod_distance: u32) {
+        self.lod_distance = lod_distance.clamp(*LOD_DISTANCE_RANGE.start(), *LOD_DISTANCE_RANGE.end());
+    }
+
+    /// `None` if distant chunks are drawn in full detail
+    pub fn lod_distance(&self) -> Option<u32> {
+        self.lod.then_some(self.lod_distance)
+    }
+
+    pub fn swap_interval(&self) -> i32 {
+        match self.vsync {
+            true => 1,
+            false => 0,
+        }
+    }
+}
+
+fn parse_f32(value: &str) -> Result<f32, String> {
+    match value.parse::<f32>() {
+        Ok(n) if n.is_finite() => Ok(n),
+        _ => Err(format!("expected a number, got `{}`", value)),
+    }
+}
@@ -0,0 +1,36 @@
+pub const TICKS_PER_SECOND: u32 = 20;
+pub const TICK_SECONDS: f32 = 1.0 / TICKS_PER_SECOND as f32;
+
+/// After a long frame the simulation falls behind instead of freezing the game
+const MAX_TICKS_PER_FRAME: u32 = 10;
+
+/// Turns frame times into a fixed number of simulation ticks per second.
+///
+/// ```
+/// # use cubic_game::tick::*;
+/// let mut clock = TickClock::default();
+///
+/// assert_eq!(clock.advance(TICK_SECONDS * 0.5), 0);
+/// assert_eq!(clock.advance(TICK_SECONDS * 2.0), 2);
+/// assert_eq!(clock.advance(60.0), 10);
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct TickClock {
+    accumulated: f32,
+}
+
+impl TickClock {
+    /// Returns how many ticks have to run after `frame_time` seconds
+    pub fn advance(&mut self, frame_time: f32) -> u32 {
+        self.accumulated += frame_time;
+
+        let ticks = (self.accumulated / TICK_SECONDS) as u32;
+        self.accumulated -= ticks as f32 * TICK_SECONDS;
+
+        if ticks > MAX_TICKS_PER_FRAME {
+            self.accumulated = 0.0;
+            return MAX_TICKS_PER_FRAME;
+        }
+        ticks
+    }
+}
@@ -0,0 +1,431 @@
+use macroquad::prelude::*;
+
+use crate::crafting::*;
+use crate::hud::*;
+use crate::inventory::*;
+use crate::item::*;
+use crate::saves::MAX_WORLD_NAME_LEN;
+use crate::settings::*;
+use crate::world::generator::GeneratorKind;
+
+mod layout;
+pub use layout::*;
+
+mod slot;
+pub use slot::*;
+
+mod screens;
+pub use screens::*;
+
+/// Canvas units
+const FONT_SIZE: f32 = 10.0;
+
+#[rustfmt::skip]
+const BACKGROUND_COLOR: Color = Color { r: 0.0, g: 0.0, b: 0.0, a: 0.5 };
+#[rustfmt::skip]
+const PANEL_COLOR: Color = Color { r: 0.25, g: 0.25, b: 0.3, a: 0.9 };
+#[rustfmt::skip]
+const BUTTON_COLOR: Color = Color { r: 0.4, g: 0.4, b: 0.45, a: 1.0 };
+#[rustfmt::skip]
+const HOVER_COLOR: Color = Color { r: 1.0, g: 1.0, b: 1.0, a: 0.25 };
+
+/// Element of a screen, laid out on the ui canvas
+#[derive(Debug, Clone, PartialEq)]
+pub struct Widget {
+    pub rect: Rect,
+    pub kind: WidgetKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum WidgetKind {
+    Panel,
+    /// Centered in its rect
+    Label(String),
+    Button(Button, String),
+    Slot(SlotRef),
+    TextField(TextField, String),
+}
+
+impl Widget {
+    pub const fn panel(rect: Rect) -> Self {
+        Self { rect, kind: WidgetKind::Panel }
+    }
+
+    pub fn label(rect: Rect, text: &str) -> Self {
+        Self { rect, kind: WidgetKind::Label(text.to_string()) }
+    }
+
+    pub fn button(rect: Rect, button: Button, text: &str) -> Self {
+        Self { rect, kind: WidgetKind::Button(button, text.to_string()) }
+    }
+
+    /// The focused field shows a caret after the text
+    pub fn text_field(rect: Rect, field: TextField, text: &str, focused: bool) -> Self {
+        let text = match focused {
+            true => format!("{}_", text),
+            false => text.to_string(),
+        };
+        Self { rect, kind: WidgetKind::TextField(field, text) }
+    }
+
+    pub const fn slot(pos: Vec2, slot: SlotRef) -> Self {
+        Self { rect: Rect { x: pos.x, y: pos.y, w: SLOT_SIZE, h: SLOT_SIZE }, kind: WidgetKind::Slot(slot) }
+    }
+}
+
+/// Mouse and keyboard state the ui reacts to in one frame
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct UiInput {
+    pub layout: Layout,
+    /// In screen pixels
+    pub mouse: Vec2,
+    pub left_pressed: bool,
+    pub right_pressed: bool,
+    /// Positive when scrolled up
+    pub wheel: f32,
+    /// Characters typed since the last frame
+    pub typed: Vec<char>,
+    pub backspace: bool,
+}
+
+impl UiInput {
+    pub fn read() -> Self {
+        Self {
+            layout: Layout::screen(),
+            mouse: mouse_position().into(),
+            left_pressed: is_mouse_button_pressed(MouseButton::Left),
+            right_pressed: is_mouse_button_pressed(MouseButton::Right),
+            wheel: mouse_wheel().1,
+            typed: std::iter::from_fn(get_char_pressed).filter(|char| !char.is_control()).collect(),
+            backspace: is_key_pressed(KeyCode::Backspace),
+        }
+    }
+}
+
+/// What the game has to do after a click in the ui
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UiEvent {
+    /// Close the screen and go back to the game
+    Close,
+    /// Save the settings and apply them to the world
+    SettingsChanged,
+    /// Back to the title screen from the game, or out of the game from the title screen
+    Quit,
+    Play(String),
+    CreateWorld { name: String, seed: String, generator: GeneratorKind },
+    RenameWorld { from: String, to: String },
+    DeleteWorld(String),
+    /// Back to the list of worlds, which may have changed
+    ShowWorlds,
+}
+
+/// Screen open on top of the game, with the widgets it was built from.
+///
+/// ```
+/// # use cubic_game::inventory::*;
+/// # use cubic_game::crafting::*;
+/// # use cubic_game::item::*;
+/// # use cubic_game::settings::*;
+/// # use cubic_game::ui::*;
+/// # use cubic_game::world::*;
+/// # use macroquad::prelude::*;
+/// let recipes = Recipes::from_config("CraftingTable = shaped Dirt Dirt / Dirt Dirt").unwrap();
+/// let mut settings = Settings::default();
+/// let mut inventory = Inventory::default();
+/// inventory.add(ItemStack::new(Item::Block(BlockType::Dirt), 4));
+///
+/// let mut ui = Ui::default();
+/// ui.open(Screen::Inventory, &settings);
+///
+/// let mut click = |ui: &mut Ui, inventory: &mut Inventory, slot: SlotRef, left_pressed: bool| {
+///     let widget = ui.widgets.iter().find(|widget| widget.kind == WidgetKind::Slot(slot)).unwrap();
+///     let input = UiInput { mouse: widget.rect.center(), left_pressed, right_pressed: !left_pressed, ..Default::default() };
+///     ui.update(&input, inventory, &recipes, &mut settings)
+/// };
+///
+/// // Drag the dirt around the crafting grid one by one
+/// click(&mut ui, &mut inventory, SlotRef::Inventory(0), true);
+/// for n in 0..4 {
+///     click(&mut ui, &mut inventory, SlotRef::Crafting(n), false);
+/// }
+/// assert_eq!(ui.cursor, None);
+///
+/// click(&mut ui, &mut inventory, SlotRef::CraftingResult, true);
+/// assert_eq!(ui.cursor, Some(ItemStack::new(Item::Block(BlockType::CraftingTable), 1)));
+///
+/// assert!(ui.close(&mut inventory).is_empty());
+/// assert!(!ui.is_open());
+/// assert_eq!(inventory.slots[0], Some(ItemStack::new(Item::Block(BlockType::CraftingTable), 1)));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Ui {
+    /// `None` while playing
+    pub screen: Option<Screen>,
+    /// Built when the screen opens or its labels change
+    pub widgets: Vec<Widget>,
+    /// Stack dragged with the mouse
+    pub cursor: Option<ItemStack>,
+    pub layout: Layout,
+    /// On the ui canvas
+    pub mouse: Vec2,
+    /// Error shown below the screen until another one opens
+    pub message: Option<String>,
+}
+
+impl Ui {
+    pub const fn is_open(&self) -> bool {
+        self.screen.is_some()
+    }
+
+    /// The world stops ticking behind the pause menu and the settings opened from it
+    pub const fn is_paused(&self) -> bool {
+        matches!(self.screen, Some(Screen::Pause | Screen::Settings))
+    }
+
+    /// Replaces the open screen, items of the old one have to be returned with [`Ui::close`] first
+    pub fn open(&mut self, screen: Screen, settings: &Settings) {
+        self.widgets = screen.build(settings);
+        self.screen = Some(screen);
+        self.message = None;
+    }
+
+    /// Builds the widgets again after the state of the screen changed
+    pub fn rebuild(&mut self, settings: &Settings) {
+        if let Some(screen) = &self.screen {
+            self.widgets = screen.build(settings);
+        }
+    }
+
+    /// Moves the items of crafting grids and the cursor back to the inventory, returns the stacks which didn't fit
+    pub fn close(&mut self, inventory: &mut Inventory) -> Vec<ItemStack> {
+        let mut stacks = inventory.return_crafting_items();
+
+        if let Some(Screen::CraftingTable(grid)) = &mut self.screen {
+            stacks.extend(grid.take_all());
+        }
+        stacks.extend(self.cursor.take());
+
+        self.screen = None;
+        self.widgets.clear();
+
+        stacks
+            .into_iter()
+            .map(|stack| ItemStack::new(stack.item, inventory.add(stack)))
+            .filter(|stack| stack.count > 0)
+            .collect()
+    }
+
+    /// Topmost widget under the mouse
+    pub fn hovered(&self) -> Option<&Widget> {
+        self.widgets.iter().rev().find(|widget| widget.rect.contains(self.mouse))
+    }
+
+    pub fn update(
+        &mut self,
+        input: &UiInput,
+        inventory: &mut Inventory,
+        recipes: &Recipes,
+        settings: &mut Settings,
+    ) -> Option<UiEvent> {
+        self.layout = input.layout;
+        self.mouse = input.layout.to_ui(input.mouse);
+
+        self.type_text(input, settings);
+        self.scroll_worlds(input.wheel, settings);
+
+        let button = match (input.left_pressed, input.right_pressed) {
+            (true, _) => MouseButton::Left,
+            (_, true) => MouseButton::Right,
+            _ => return None,
+        };
+
+        match self.hovered()?.kind.clone() {
+            WidgetKind::Slot(slot) => {
+                self.click(slot, button, inventory, recipes);
+                None
+            }
+            WidgetKind::Button(clicked, _) if button == MouseButton::Left => self.press(clicked, settings),
+            WidgetKind::TextField(field, _) => {
+                if let Some(Screen::CreateWorld { focused, .. }) = &mut self.screen {
+                    *focused = field;
+                }
+                self.rebuild(settings);
+                None
+            }
+            _ => None,
+        }
+    }
+
+    fn type_text(&mut self, input: &UiInput, settings: &Settings) {
+        let Some(text) = self.screen.as_mut().and_then(Screen::focused_text_mut) else { return };
+        if input.typed.is_empty() && !input.backspace {
+            return;
+        }
+
+        if input.backspace {
+            text.pop();
+        }
+        for char in &input.typed {
+            if text.chars().count() < MAX_WORLD_NAME_LEN {
+                text.push(*char);
+            }
+        }
+        self.rebuild(settings);
+    }
+
+    fn scroll_worlds(&mut self, wheel: f32, settings: &Settings) {
+        let Some(Screen::Title { worlds, scroll, .. }) = &mut self.screen else { return };
+        if wheel == 0.0 {
+            return;
+        }
+
+        let max_scroll = worlds.len().saturating_sub(VISIBLE_WORLDS);
+        *scroll = match wheel > 0.0 {
+            true => scroll.saturating_sub(1),
+            false => (*scroll + 1).min(max_scroll),
+        };
+        self.rebuild(settings);
+    }
+
+    fn click(&mut self, slot: SlotRef, button: MouseButton, inventory: &mut Inventory, recipes: &Recipes) {
+        let grid = match &mut self.screen {
+            Some(Screen::CraftingTable(grid)) => grid,
+            _ => &mut inventory.crafting,
+        };
+
+        match slot {
+            SlotRef::Inventory(n) => click_slot(&mut inventory.slots[n], &mut self.cursor, button),
+            SlotRef::Crafting(n) => click_slot(&mut grid.slots[n], &mut self.cursor, button),
+            SlotRef::CraftingResult => take_crafting_result(grid, recipes, &mut self.cursor),
+        }
+    }
+
+    fn press(&mut self, button: Button, settings: &mut Settings) -> Option<UiEvent> {
+        match button {
+            Button::Resume => return Some(UiEvent::Close),
+            Button::Quit => return Some(UiEvent::Quit),
+            Button::Settings => self.open(Screen::Settings, settings),
+            Button::Back => self.open(Screen::Pause, settings),
+            Button::Decrease(setting) | Button::Increase(setting) => {
+                let steps = if matches!(button, Button::Increase(_)) { 1 } else { -1 };
+                setting.adjust(settings, steps);
+                self.open(Screen::Settings, settings);
+                return Some(UiEvent::SettingsChanged);
+            }
+            Button::SelectWorld(n) => {
+                if let Some(Screen::Title { selected, .. }) = &mut self.screen {
+                    *selected = Some(n);
+                }
+                self.rebuild(settings);
+            }
+            Button::Play => return self.selected_world().map(UiEvent::Play),
+            Button::NewWorld => self.open(Screen::create_world(), settings),
+            Button::RenameWorld => {
+                if let Some(name) = self.selected_world() {
+                    self.open(Screen::RenameWorld { from: name.clone(), name }, settings);
+                }
+            }
+            Button::DeleteWorld => {
+                if let Some(name) = self.selected_world() {
+                    self.open(Screen::DeleteWorld(name), settings);
+                }
+            }
+            Button::CycleGenerator => {
+                if let Some(Screen::CreateWorld { generator, .. }) = &mut self.screen {
+                    *generator = generator.next();
+                }
+                self.rebuild(settings);
+            }
+            Button::Confirm => {
+                return match self.screen.clone()? {
+                    Screen::CreateWorld { name, seed, generator, .. } => Some(UiEvent::CreateWorld { name, seed, generator }),
+                    Screen::RenameWorld { from, name } => Some(UiEvent::RenameWorld { from, to: name }),
+                    Screen::DeleteWorld(name) => Some(UiEvent::DeleteWorld(name)),
+                    _ => None,
+                };
+            }
+            Button::Cancel => return Some(UiEvent::ShowWorlds),
+        }
+        None
+    }
+
+    fn selected_world(&self) -> Option<String> {
+        self.screen.as_ref()?.selected_world().map(String::from)
+    }
+
+    /// Stack shown in the slot, the crafting result is what the grid would make now
+    pub fn slot_stack(&self, slot: SlotRef, inventory: &Inventory, recipes: &Recipes) -> Option<ItemStack> {
+        let grid = match &self.screen {
+            Some(Screen::CraftingTable(grid)) => grid,
+            _ => &inventory.crafting,
+        };
+
+        match slot {
+            SlotRef::Inventory(n) => inventory.slots[n],
+            SlotRef::Crafting(n) => grid.slots[n],
+            SlotRef::CraftingResult => grid.result(recipes),
+        }
+    }
+
+    /// Darkens the game and draws the screen on top, with the dragged stack under the mouse
+    pub fn draw(&self, inventory: &Inventory, recipes: &Recipes, atlas: &Texture2D) {
+        if !self.is_open() {
+            return;
+        }
+
+        draw_rectangle(0.0, 0.0, screen_width(), screen_height(), BACKGROUND_COLOR);
+
+        let hovered = self.hovered();
+        let scale = self.layout.scale;
+
+        for widget in &self.widgets {
+            let rect = self.layout.to_screen(widget.rect);
+            let is_hovered = hovered == Some(widget);
+
+            match &widget.kind {
+                WidgetKind::Panel => {
+                    draw_rectangle(rect.x, rect.y, rect.w, rect.h, PANEL_COLOR);
+                    draw_rectangle_lines(rect.x, rect.y, rect.w, rect.h, scale, BLACK);
+                }
+                WidgetKind::Label(text) => draw_centered_text(text, rect, scale),
+                WidgetKind::Button(_, text) => {
+                    draw_rectangle(rect.x, rect.y, rect.w, rect.h, BUTTON_COLOR);
+                    draw_rectangle_lines(rect.x, rect.y, rect.w, rect.h, scale, BLACK);
+                    draw_centered_text(text, rect, scale);
+                }
+                WidgetKind::Slot(slot) => {
+                    draw_rectangle(rect.x, rect.y, rect.w, rect.h, SLOT_COLOR);
+                    draw_rectangle_lines(rect.x, rect.y, rect.w, rect.h, scale / 2.0, PANEL_COLOR);
+                    draw_item_stack(self.slot_stack(*slot, inventory, recipes), rect, atlas);
+                }
+                WidgetKind::TextField(_, text) => {
+                    draw_rectangle(rect.x, rect.y, rect.w, rect.h, SLOT_COLOR);
+                    draw_rectangle_lines(rect.x, rect.y, rect.w, rect.h, scale / 2.0, WHITE);
+                    draw_centered_text(text, rect, scale);
+                }
+            }
+
+            if is_hovered && matches!(widget.kind, WidgetKind::Button(..) | WidgetKind::Slot(_)) {
+                draw_rectangle(rect.x, rect.y, rect.w, rect.h, HOVER_COLOR);
+            }
+        }
+
+        if let Some(message) = &self.message {
+            let below = Rect::new(0.0, UI_HEIGHT - 24.0, UI_WIDTH, 20.0);
+            draw_centered_text(message, self.layout.to_screen(below), scale);
+        }
+
+        let size = SLOT_SIZE * scale;
+        let held = Rect::new(0.0, 0.0, size, size).offset(self.layout.offset + self.mouse * scale - size / 2.0);
+        draw_item_stack(self.cursor, held, atlas);
+    }
+}
+
+fn draw_centered_text(text: &str, rect: Rect, scale: f32) {
+    let font_size = (FONT_SIZE * scale) as u16;
+    let size = measure_text(text, None, font_size, 1.0);
+
+    let x = rect.x + (rect.w - size.width) / 2.0;
+    let y = rect.y + (rect.h + size.height) / 2.0;
+    draw_text(text, x, y, font_size as f32, WHITE);
+}
@@ -0,0 +1,61 @@
+use super::*;
+
+/// Size of the virtual canvas widgets are laid out on
+pub const UI_WIDTH: f32 = 480.0;
+pub const UI_HEIGHT: f32 = 270.0;
+
+/// Maps the virtual canvas onto the window, scaled to fit and centered.
+///
+/// ```
+/// # use cubic_game::ui::*;
+/// # use macroquad::prelude::*;
+/// let layout = Layout::new(1920.0, 1200.0);
+///
+/// assert_eq!(layout.scale, 4.0);
+/// assert_eq!(layout.to_screen(Rect::new(0.0, 0.0, UI_WIDTH, UI_HEIGHT)), Rect::new(0.0, 60.0, 1920.0, 1080.0));
+/// assert_eq!(layout.to_ui(vec2(960.0, 600.0)), vec2(UI_WIDTH / 2.0, UI_HEIGHT / 2.0));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Layout {
+    /// Screen pixels per canvas unit
+    pub scale: f32,
+    /// Top left corner of the canvas on the screen
+    pub offset: Vec2,
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Self::new(UI_WIDTH, UI_HEIGHT)
+    }
+}
+
+impl Layout {
+    pub fn new(screen_width: f32, screen_height: f32) -> Self {
+        let scale = (screen_width / UI_WIDTH).min(screen_height / UI_HEIGHT);
+        let offset = (vec2(screen_width, screen_height) - vec2(UI_WIDTH, UI_HEIGHT) * scale) / 2.0;
+        Self { scale, offset }
+    }
+
+    /// Layout of the current window
+    pub fn screen() -> Self {
+        Self::new(screen_width(), screen_height())
+    }
+
+    pub fn to_screen(&self, rect: Rect) -> Rect {
+        Rect::new(
+            self.offset.x + rect.x * self.scale,
+            self.offset.y + rect.y * self.scale,
+            rect.w * self.scale,
+            rect.h * self.scale,
+        )
+    }
+
+    pub fn to_ui(&self, pos: Vec2) -> Vec2 {
+        (pos - self.offset) / self.scale
+    }
+}
+
+/// Rect of the given size in the middle of the canvas
+pub fn centered(w: f32, h: f32) -> Rect {
+    Rect::new((UI_WIDTH - w) / 2.0, (UI_HEIGHT - h) / 2.0, w, h)
+}
@@ -0,0 +1,327 @@
+use super::*;
+
+const INVENTORY_PANEL: Rect = Rect { x: 140.0, y: 43.0, w: 200.0, h: 184.0 };
+const BUTTON_WIDTH: f32 = 140.0;
+const ROW_HEIGHT: f32 = 26.0;
+/// Worlds shown in the title screen at once
+pub const VISIBLE_WORLDS: usize = 6;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Screen {
+    /// Inventory with its 2x2 crafting grid
+    Inventory,
+    /// 3x3 grid of a crafting table, emptied into the inventory when closed
+    CraftingTable(CraftingGrid),
+    Pause,
+    Settings,
+    /// Saved worlds, shown before playing
+    Title {
+        worlds: Vec<String>,
+        selected: Option<usize>,
+        /// First world shown in the list
+        scroll: usize,
+    },
+    CreateWorld {
+        name: String,
+        seed: String,
+        generator: GeneratorKind,
+        focused: TextField,
+    },
+    RenameWorld {
+        from: String,
+        name: String,
+    },
+    DeleteWorld(String),
+}
+
+/// Text field which receives typed characters
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TextField {
+    #[default]
+    Name,
+    Seed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Button {
+    Resume,
+    Settings,
+    /// From settings back to the pause menu
+    Back,
+    Quit,
+    Decrease(Setting),
+    Increase(Setting),
+    /// Index into the worlds of the title screen
+    SelectWorld(usize),
+    Play,
+    NewWorld,
+    RenameWorld,
+    DeleteWorld,
+    CycleGenerator,
+    /// Creates, renames or deletes the world, depending on the screen
+    Confirm,
+    /// Back to the title screen
+    Cancel,
+}
+
+/// Settings which can be changed in game
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Setting {
+    Fov,
+    Sensitivity,
+    RenderDistance,
+}
+
+impl Setting {
+    pub const ALL: [Setting; 3] = [Setting::Fov, Setting::Sensitivity, Setting::RenderDistance];
+
+    pub fn label(self, settings: &Settings) -> String {
+        match self {
+            Setting::Fov => format!("FOV: {:.0}", settings.fov),
+            Setting::Sensitivity => format!("Sensitivity: {:.2}", settings.sensitivity),
+            Setting::RenderDistance => format!("Render distance: {}", settings.render_distance),
+        }
+    }
+
+    /// `steps` clicks of `+`, negative for `-`
+    pub fn adjust(self, settings: &mut Settings, steps: i32) {
+        match self {
+            Setting::Fov => settings.set_fov(settings.fov + 5.0 * steps as f32),
+            Setting::Sensitivity => settings.set_sensitivity(settings.sensitivity + 0.01 * steps as f32),
+            Setting::RenderDistance => {
+                settings.set_render_distance(settings.render_distance.saturating_add_signed(steps))
+            }
+        }
+    }
+}
+
+impl Screen {
+    pub fn crafting_table() -> Self {
+        Screen::CraftingTable(CraftingGrid::new(TABLE_GRID_SIZE))
+    }
+
+    pub fn title(worlds: Vec<String>) -> Self {
+        Screen::Title { worlds, selected: None, scroll: 0 }
+    }
+
+    pub fn create_world() -> Self {
+        Screen::CreateWorld {
+            name: String::from("New World"),
+            seed: String::new(),
+            generator: GeneratorKind::default(),
+            focused: TextField::Name,
+        }
+    }
+
+    /// Text of the field characters are typed into
+    pub fn focused_text_mut(&mut self) -> Option<&mut String> {
+        match self {
+            Screen::CreateWorld { name, focused: TextField::Name, .. } => Some(name),
+            Screen::CreateWorld { seed, focused: TextField::Seed, .. } => Some(seed),
+            Screen::RenameWorld { name, .. } => Some(name),
+            _ => None,
+        }
+    }
+
+    /// World selected on the title screen
+    pub fn selected_world(&self) -> Option<&str> {
+        match self {
+            Screen::Title { worlds, selected: Some(n), .. } => worlds.get(*n).map(String::as_str),
+            _ => None,
+        }
+    }
+
+    pub const fn heading(&self) -> &'static str {
+        match self {
+            Screen::Inventory => "Inventory",
+            Screen::CraftingTable(_) => "Crafting",
+            Screen::Pause => "Game paused",
+            Screen::Settings => "Settings",
+            Screen::Title { .. } => "Select world",
+            Screen::CreateWorld { .. } => "Create world",
+            Screen::RenameWorld { .. } => "Rename world",
+            Screen::DeleteWorld(_) => "Delete world",
+        }
+    }
+
+    /// Size of the crafting grid shown on the screen
+    pub const fn grid_size(&self) -> Option<usize> {
+        match self {
+            Screen::Inventory => Some(INVENTORY_GRID_SIZE),
+            Screen::CraftingTable(_) => Some(TABLE_GRID_SIZE),
+            _ => None,
+        }
+    }
+
+    /// Widgets of the screen, labels show the current settings
+    pub fn build(&self, settings: &Settings) -> Vec<Widget> {
+        match self {
+            Screen::Inventory | Screen::CraftingTable(_) => self.build_inventory(),
+            Screen::Pause => self.build_menu(&[
+                (Button::Resume, "Back to game"),
+                (Button::Settings, "Settings"),
+                (Button::Quit, "Save and quit to title"),
+            ]),
+            Screen::Settings => self.build_settings(settings),
+            Screen::Title { worlds, selected, scroll } => self.build_title(worlds, *selected, *scroll),
+            Screen::CreateWorld { name, seed, generator, focused } => self.build_create_world(name, seed, *generator, *focused),
+            Screen::RenameWorld { name, .. } => {
+                let mut widgets = self.build_dialog(&[(Button::Confirm, "Rename"), (Button::Cancel, "Cancel")]);
+                let panel = widgets[0].rect;
+                widgets.push(Widget::text_field(Rect::new(panel.x + 10.0, panel.y + 24.0, panel.w - 20.0, SLOT_SIZE), TextField::Name, name, true));
+                widgets
+            }
+            Screen::DeleteWorld(name) => {
+                let mut widgets = self.build_dialog(&[(Button::Confirm, "Delete"), (Button::Cancel, "Cancel")]);
+                let panel = widgets[0].rect;
+                widgets.push(Widget::label(Rect::new(panel.x, panel.y + 24.0, panel.w, SLOT_SIZE), &format!("`{}` will be lost forever", name)));
+                widgets
+            }
+        }
+    }
+
+    fn build_title(&self, worlds: &[String], selected: Option<usize>, scroll: usize) -> Vec<Widget> {
+        let panel = centered(300.0, 220.0);
+        let mut widgets = vec![
+            Widget::panel(panel),
+            Widget::label(Rect::new(panel.x, panel.y + 6.0, panel.w, 12.0), self.heading()),
+        ];
+
+        if worlds.is_empty() {
+            widgets.push(Widget::label(Rect::new(panel.x, panel.y + 80.0, panel.w, 12.0), "No worlds yet"));
+        }
+
+        for (n, name) in worlds.iter().enumerate().skip(scroll).take(VISIBLE_WORLDS) {
+            let y = panel.y + 24.0 + (n - scroll) as f32 * 22.0;
+            let text = match selected == Some(n) {
+                true => format!("> {} <", name),
+                false => name.clone(),
+            };
+            widgets.push(Widget::button(Rect::new(panel.x + 10.0, y, panel.w - 20.0, SLOT_SIZE), Button::SelectWorld(n), &text));
+        }
+
+        widgets.extend(button_row(panel, panel.y + 164.0, &[(Button::Play, "Play"), (Button::NewWorld, "New world")]));
+        widgets.extend(button_row(panel, panel.y + 190.0, &[
+            (Button::RenameWorld, "Rename"),
+            (Button::DeleteWorld, "Delete"),
+            (Button::Quit, "Quit game"),
+        ]));
+        widgets
+    }
+
+    fn build_create_world(&self, name: &str, seed: &str, generator: GeneratorKind, focused: TextField) -> Vec<Widget> {
+        let panel = centered(240.0, 140.0);
+        let mut widgets = vec![
+            Widget::panel(panel),
+            Widget::label(Rect::new(panel.x, panel.y + 6.0, panel.w, 12.0), self.heading()),
+        ];
+
+        for (n, (field, label, text)) in [(TextField::Name, "Name", name), (TextField::Seed, "Seed", seed)].into_iter().enumerate() {
+            let y = panel.y + 24.0 + n as f32 * ROW_HEIGHT;
+            widgets.push(Widget::label(Rect::new(panel.x + 10.0, y, 50.0, SLOT_SIZE), label));
+            widgets.push(Widget::text_field(Rect::new(panel.x + 64.0, y, panel.w - 74.0, SLOT_SIZE), field, text, field == focused));
+        }
+
+        let generator_button = Rect::new(panel.x + 10.0, panel.y + 24.0 + 2.0 * ROW_HEIGHT, panel.w - 20.0, SLOT_SIZE);
+        widgets.push(Widget::button(generator_button, Button::CycleGenerator, &format!("Generator: {}", generator)));
+
+        widgets.extend(button_row(panel, panel.y + 110.0, &[(Button::Confirm, "Create"), (Button::Cancel, "Cancel")]));
+        widgets
+    }
+
+    /// Panel with a heading, one row of content and a row of buttons
+    fn build_dialog(&self, buttons: &[(Button, &str)]) -> Vec<Widget> {
+        let panel = centered(240.0, 80.0);
+        let mut widgets = vec![
+            Widget::panel(panel),
+            Widget::label(Rect::new(panel.x, panel.y + 6.0, panel.w, 12.0), self.heading()),
+        ];
+        widgets.extend(button_row(panel, panel.y + 50.0, buttons));
+        widgets
+    }
+
+    fn build_inventory(&self) -> Vec<Widget> {
+        let panel = INVENTORY_PANEL;
+        let mut widgets = vec![
+            Widget::panel(panel),
+            Widget::label(Rect::new(panel.x, panel.y + 4.0, panel.w, 12.0), self.heading()),
+        ];
+
+        if let Some(size) = self.grid_size() {
+            let grid = vec2(panel.x + 40.0, panel.y + 20.0);
+            let grid_width = size as f32 * SLOT_SIZE;
+
+            for n in 0..size * size {
+                let (x, y) = ((n % size) as f32, (n / size) as f32);
+                widgets.push(Widget::slot(grid + vec2(x, y) * SLOT_SIZE, SlotRef::Crafting(n)));
+            }
+
+            let result = grid + vec2(grid_width + 24.0, (grid_width - SLOT_SIZE) / 2.0);
+            widgets.push(Widget::label(Rect::new(grid.x + grid_width, result.y, 24.0, SLOT_SIZE), "->"));
+            widgets.push(Widget::slot(result, SlotRef::CraftingResult));
+        }
+
+        // Hotbar goes below the rest of the inventory
+        for n in 0..INVENTORY_SIZE {
+            let row = match n < HOTBAR_SIZE {
+                true => 3.3,
+                false => (n / HOTBAR_SIZE - 1) as f32,
+            };
+            let pos = vec2(panel.x + 10.0 + (n % HOTBAR_SIZE) as f32 * SLOT_SIZE, panel.y + 88.0 + row * SLOT_SIZE);
+            widgets.push(Widget::slot(pos, SlotRef::Inventory(n)));
+        }
+        widgets
+    }
+
+    fn build_menu(&self, buttons: &[(Button, &str)]) -> Vec<Widget> {
+        let panel = centered(BUTTON_WIDTH + 20.0, 28.0 + buttons.len() as f32 * ROW_HEIGHT);
+        let mut widgets = vec![
+            Widget::panel(panel),
+            Widget::label(Rect::new(panel.x, panel.y + 6.0, panel.w, 12.0), self.heading()),
+        ];
+
+        for (n, (button, text)) in buttons.iter().enumerate() {
+            let rect = Rect::new(panel.x + 10.0, panel.y + 24.0 + n as f32 * ROW_HEIGHT, BUTTON_WIDTH, SLOT_SIZE);
+            widgets.push(Widget::button(rect, *button, text));
+        }
+        widgets
+    }
+
+    fn build_settings(&self, settings: &Settings) -> Vec<Widget> {
+        let rows = Setting::ALL.len() + 1;
+        let panel = centered(220.0, 28.0 + rows as f32 * ROW_HEIGHT);
+        let mut widgets = vec![
+            Widget::panel(panel),
+            Widget::label(Rect::new(panel.x, panel.y + 6.0, panel.w, 12.0), self.heading()),
+        ];
+
+        for (n, setting) in Setting::ALL.into_iter().enumerate() {
+            let y = panel.y + 24.0 + n as f32 * ROW_HEIGHT;
+            let value = Rect::new(panel.x + 34.0, y, panel.w - 68.0, SLOT_SIZE);
+
+            widgets.push(Widget::button(Rect::new(panel.x + 10.0, y, SLOT_SIZE, SLOT_SIZE), Button::Decrease(setting), "-"));
+            widgets.push(Widget::label(value, &setting.label(settings)));
+            widgets.push(Widget::button(Rect::new(value.right() + 4.0, y, SLOT_SIZE, SLOT_SIZE), Button::Increase(setting), "+"));
+        }
+
+        let y = panel.y + 24.0 + Setting::ALL.len() as f32 * ROW_HEIGHT;
+        let back = Rect::new(panel.x + (panel.w - BUTTON_WIDTH) / 2.0, y, BUTTON_WIDTH, SLOT_SIZE);
+        widgets.push(Widget::button(back, Button::Back, "Done"));
+        widgets
+    }
+}
+
+/// Buttons of the same width across the panel
+fn button_row(panel: Rect, y: f32, buttons: &[(Button, &str)]) -> Vec<Widget> {
+    let gap = 8.0;
+    let width = (panel.w - 20.0 - gap * (buttons.len() as f32 - 1.0)) / buttons.len() as f32;
+
+    buttons
+        .iter()
+        .enumerate()
+        .map(|(n, (button, text))| {
+            let rect = Rect::new(panel.x + 10.0 + n as f32 * (width + gap), y, width, SLOT_SIZE);
+            Widget::button(rect, *button, text)
+        })
+        .collect()
+}
@@ -0,0 +1,91 @@
+use super::*;
+
+/// Size of a slot on the canvas
+pub const SLOT_SIZE: f32 = 20.0;
+
+/// Where the items shown in a slot widget live
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SlotRef {
+    /// Index into [`Inventory::slots`]
+    Inventory(usize),
+    /// Index into the crafting grid of the open screen
+    Crafting(usize),
+    /// Output of the crafting grid, it can only be taken from
+    CraftingResult,
+}
+
+/// Drag and drop between a slot and the stack held by the cursor.
+/// Left button swaps or merges the stacks, right button takes half a stack or puts down a single item.
+///
+/// ```
+/// # use cubic_game::item::*;
+/// # use cubic_game::ui::*;
+/// # use cubic_game::world::*;
+/// # use macroquad::prelude::MouseButton;
+/// let stone = Item::Block(BlockType::Stone);
+/// let mut slot = Some(ItemStack::new(stone, 10));
+/// let mut cursor = None;
+///
+/// click_slot(&mut slot, &mut cursor, MouseButton::Right);
+/// assert_eq!((slot, cursor), (Some(ItemStack::new(stone, 5)), Some(ItemStack::new(stone, 5))));
+///
+/// click_slot(&mut slot, &mut cursor, MouseButton::Right);
+/// assert_eq!((slot, cursor), (Some(ItemStack::new(stone, 6)), Some(ItemStack::new(stone, 4))));
+///
+/// click_slot(&mut slot, &mut cursor, MouseButton::Left);
+/// assert_eq!((slot, cursor), (Some(ItemStack::new(stone, 10)), None));
+///
+/// click_slot(&mut slot, &mut cursor, MouseButton::Left);
+/// assert_eq!((slot, cursor), (None, Some(ItemStack::new(stone, 10))));
+/// ```
+pub fn click_slot(slot: &mut Option<ItemStack>, cursor: &mut Option<ItemStack>, button: MouseButton) {
+    match (slot.as_mut(), cursor.as_mut(), button) {
+        (Some(below), Some(held), MouseButton::Left) if below.item == held.item => {
+            let moved = held.count.min(below.item.max_stack_size().saturating_sub(below.count));
+            below.count += moved;
+            held.count -= moved;
+        }
+        (Some(below), None, MouseButton::Right) => {
+            let taken = below.count.div_ceil(2);
+            below.count -= taken;
+            *cursor = Some(ItemStack::new(below.item, taken));
+        }
+        (None, Some(held), MouseButton::Right) => {
+            held.count -= 1;
+            *slot = Some(ItemStack::new(held.item, 1));
+        }
+        (Some(below), Some(held), MouseButton::Right) if below.item == held.item => {
+            if below.count < below.item.max_stack_size() {
+                below.count += 1;
+                held.count -= 1;
+            }
+        }
+        _ => std::mem::swap(slot, cursor),
+    }
+
+    for stack in [slot, cursor] {
+        if stack.is_some_and(|stack| stack.count == 0) {
+            *stack = None;
+        }
+    }
+}
+
+/// Crafts into the cursor if the result fits on the held stack
+pub fn take_crafting_result(grid: &mut CraftingGrid, recipes: &Recipes, cursor: &mut Option<ItemStack>) {
+    let Some(result) = grid.result(recipes) else { return };
+
+    let fits = match cursor {
+        Some(held) => held.item == result.item && held.count + result.count <= held.item.max_stack_size(),
+        None => true,
+    };
+    if !fits {
+        return;
+    }
+
+    if let Some(result) = grid.craft(recipes) {
+        match cursor {
+            Some(held) => held.count += result.count,
+            None => *cursor = Some(result),
+        }
+    }
+}
@@ -1,24 +1,76 @@
-use std::{array::from_fn as arr_fn, ops::{Deref, DerefMut}};
+use std::array::from_fn as arr_fn;
+use std::fmt::Display;
+use std::ops::Add;
+use std::str::FromStr;
 
-use derive_more::{Deref, DerefMut};
+use macroquad::prelude::*;
 
 pub mod render;
 
-const CHUNK_SIZE_16: usize = 16;
+mod store;
+pub use store::World;
+
+pub mod light;
+use light::*;
+
+mod raycast;
+pub use raycast::{raycast, RayHit};
+
+pub mod time;
+use time::WorldTime;
+
+pub mod fluid;
+use fluid::Fluid;
+
+pub mod ticks;
+use ticks::ScheduledTicks;
+
+pub mod entity;
+use entity::Entities;
+
+pub mod falling_block;
+
+pub mod item_entity;
+
+pub mod generator;
+
+mod edit;
+pub use edit::BlockRegion;
+
+pub const CHUNK_SIZE_16: usize = 16;
 
 #[derive(Debug, Default, PartialEq, Eq, Clone)]
 pub struct BlockState {
     pub block_type: BlockType,
+    /// Fluids: 0 is a source, 1..=7 is the distance it has flown and [`fluid::FALLING`] is falling down.
+    /// Crops: growth stage up to [`ticks::MAX_CROP_AGE`]
+    pub level: u8,
 }
 
 impl BlockState {
     pub const fn new(block_type: BlockType) -> Self {
-        Self { block_type }
+        Self { block_type, level: 0 }
+    }
+
+    pub const fn with_level(block_type: BlockType, level: u8) -> Self {
+        Self { block_type, level }
+    }
+
+    pub const fn fluid(&self) -> Option<Fluid> {
+        self.block_type.fluid()
     }
 
     pub const fn is_empty(&self) -> bool {
         self.block_type.is_empty()
     }
+
+    pub const fn is_opaque(&self) -> bool {
+        self.block_type.is_opaque()
+    }
+
+    pub const fn light_emission(&self) -> u8 {
+        self.block_type.light_emission()
+    }
 }
 
 impl BlockState {
@@ -29,21 +81,101 @@ impl BlockState {
     pub const DIRT: BlockState = BlockState::new(BlockType::Dirt);
     pub const GRASS: BlockState = BlockState::new(BlockType::Grass);
     pub const SAND: BlockState = BlockState::new(BlockType::Sand);
+    pub const GRAVEL: BlockState = BlockState::new(BlockType::Gravel);
+    pub const TORCH: BlockState = BlockState::new(BlockType::Torch);
+    pub const WATER: BlockState = BlockState::new(BlockType::Water);
+    pub const LAVA: BlockState = BlockState::new(BlockType::Lava);
+    pub const WHEAT: BlockState = BlockState::new(BlockType::Wheat);
+    pub const CRAFTING_TABLE: BlockState = BlockState::new(BlockType::CraftingTable);
 }
 
-#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy, Hash)]
 pub enum BlockType {
     #[default] Air,
     Dirt,
     Grass,
     Stone,
     Sand,
+    Gravel,
+    Torch,
+    Water,
+    Lava,
+    Wheat,
+    CraftingTable,
 }
 
 impl BlockType {
+    pub const ALL: [BlockType; 11] = [
+        BlockType::Air,
+        BlockType::Dirt,
+        BlockType::Grass,
+        BlockType::Stone,
+        BlockType::Sand,
+        BlockType::Gravel,
+        BlockType::Torch,
+        BlockType::Water,
+        BlockType::Lava,
+        BlockType::Wheat,
+        BlockType::CraftingTable,
+    ];
+
     pub const fn is_empty(&self) -> bool {
         matches!(self, BlockType::Air)
     }
+
+    /// Opaque blocks hide faces of their neighbours and stop light
+    pub const fn is_opaque(&self) -> bool {
+        !matches!(self, BlockType::Air | BlockType::Torch | BlockType::Water | BlockType::Lava | BlockType::Wheat)
+    }
+
+    pub const fn light_emission(&self) -> u8 {
+        match self {
+            BlockType::Torch => 14,
+            BlockType::Lava => 15,
+            _ => 0,
+        }
+    }
+
+    pub const fn fluid(&self) -> Option<Fluid> {
+        match self {
+            BlockType::Water => Some(Fluid::Water),
+            BlockType::Lava => Some(Fluid::Lava),
+            _ => None,
+        }
+    }
+
+    /// Falls when there is nothing below it
+    pub const fn has_gravity(&self) -> bool {
+        matches!(self, BlockType::Sand | BlockType::Gravel)
+    }
+
+    /// Ticks until a block which reacts to its neighbours is updated after one of them changes
+    pub const fn tick_delay(&self) -> Option<u64> {
+        match self {
+            BlockType::Water => Some(Fluid::Water.tick_delay()),
+            BlockType::Lava => Some(Fluid::Lava.tick_delay()),
+            BlockType::Sand | BlockType::Gravel => Some(2),
+            BlockType::Wheat => Some(1),
+            _ => None,
+        }
+    }
+}
+
+impl Display for BlockType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl FromStr for BlockType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        BlockType::ALL
+            .into_iter()
+            .find(|block_type| block_type.to_string() == s)
+            .ok_or_else(|| format!("unknown block `{}`", s))
+    }
 }
 
 #[derive(Debug, Default, PartialEq, Eq, Clone)]
@@ -58,6 +190,8 @@ pub enum Biome {
 pub struct Chunk {
     pub biome: Biome,
     pub blocks: [ChunkLayer; CHUNK_SIZE_16],
+    /// Computed by the world when the chunk is inserted
+    pub light: ChunkLight,
 }
 
 impl Chunk {
@@ -66,6 +200,7 @@ impl Chunk {
         Chunk {
             biome: Biome::Plains,
             blocks: arr_fn(|y| ChunkLayer::from_fn(|x, z| func(x, y, z))),
+            light: ChunkLight::DARK,
         }
     }
 
@@ -86,6 +221,7 @@ impl Chunk {
     pub const EMPTY: Chunk = Chunk {
         biome: Biome::Plains,
         blocks: [ChunkLayer::EMPTY; CHUNK_SIZE_16],
+        light: ChunkLight::DARK,
     };
 }
 
@@ -124,6 +260,135 @@ impl ChunkLayer {
 
     pub const EMPTY: ChunkLayer =
         ChunkLayer([const {[BlockState::AIR; CHUNK_SIZE_16]}; CHUNK_SIZE_16]);
+}
 
-    
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ChunkPos {
+    pub x: isize,
+    pub y: isize,
+    pub z: isize,
+}
+
+impl ChunkPos {
+    pub const fn new(x: isize, y: isize, z: isize) -> Self {
+        Self { x, y, z }
+    }
+
+    pub const fn neighbour(self, face: Face) -> ChunkPos {
+        let (dx, dy, dz) = face.offset();
+        ChunkPos::new(self.x + dx, self.y + dy, self.z + dz)
+    }
+
+    /// Center of the chunk in world coordinates
+    pub fn center(self) -> Vec3 {
+        let BlockPos { x, y, z } = self.into();
+        let half = CHUNK_SIZE_16 as f32 / 2.0;
+        vec3(x as f32 + half, y as f32 + half, z as f32 + half)
+    }
+
+    /// (min, max) corners of the chunk in world coordinates
+    pub fn aabb(self) -> (Vec3, Vec3) {
+        let BlockPos { x, y, z } = self.into();
+        let min = vec3(x as f32, y as f32, z as f32);
+        (min, min + Vec3::splat(CHUNK_SIZE_16 as f32))
+    }
+}
+
+impl From<ChunkPos> for BlockPos {
+    fn from(chunk_pos: ChunkPos) -> BlockPos {
+        let ChunkPos { x, y, z } = chunk_pos;
+        BlockPos {
+            x: x * CHUNK_SIZE_16 as isize,
+            y: y * CHUNK_SIZE_16 as isize,
+            z: z * CHUNK_SIZE_16 as isize,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BlockPos {
+    pub x: isize,
+    pub y: isize,
+    pub z: isize,
+}
+
+impl BlockPos {
+    pub const fn new(x: isize, y: isize, z: isize) -> Self {
+        Self { x, y, z }
+    }
+
+    /// Block which contains the point
+    pub fn from_vec3(pos: Vec3) -> Self {
+        let pos = pos.floor();
+        Self::new(pos.x as isize, pos.y as isize, pos.z as isize)
+    }
+
+    pub const fn chunk_pos(self) -> ChunkPos {
+        let size = CHUNK_SIZE_16 as isize;
+        ChunkPos::new(self.x.div_euclid(size), self.y.div_euclid(size), self.z.div_euclid(size))
+    }
+
+    /// (usize, usize, usize) - pos in chunk 0..16
+    pub const fn in_chunk(self) -> (usize, usize, usize) {
+        let size = CHUNK_SIZE_16 as isize;
+        (self.x.rem_euclid(size) as usize, self.y.rem_euclid(size) as usize, self.z.rem_euclid(size) as usize)
+    }
+
+    pub const fn neighbour(self, face: Face) -> BlockPos {
+        let (dx, dy, dz) = face.offset();
+        BlockPos::new(self.x + dx, self.y + dy, self.z + dz)
+    }
+}
+
+impl Add for BlockPos {
+    type Output = BlockPos;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        let BlockPos { x, y, z } = self;
+        BlockPos {
+            x: x + rhs.x,
+            y: y + rhs.y,
+            z: z + rhs.z,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Face {
+    Top,
+    Bottom,
+    Px,
+    Nx,
+    Pz,
+    Nz,
+}
+
+impl Face {
+    pub const ALL: [Face; 6] = [Face::Top, Face::Bottom, Face::Px, Face::Nx, Face::Pz, Face::Nz];
+
+    pub const fn offset(self) -> (isize, isize, isize) {
+        match self {
+            Face::Top => (0, 1, 0),
+            Face::Bottom => (0, -1, 0),
+            Face::Px => (1, 0, 0),
+            Face::Nx => (-1, 0, 0),
+            Face::Pz => (0, 0, 1),
+            Face::Nz => (0, 0, -1),
+        }
+    }
+
+    pub const fn opposite(self) -> Face {
+        match self {
+            Face::Top => Face::Bottom,
+            Face::Bottom => Face::Top,
+            Face::Px => Face::Nx,
+            Face::Nx => Face::Px,
+            Face::Pz => Face::Nz,
+            Face::Nz => Face::Pz,
+        }
+    }
+
+    pub const fn index(self) -> usize {
+        self as usize
+    }
 }
@@ -0,0 +1,150 @@
+use std::collections::HashSet;
+
+use super::*;
+
+/// Box of blocks between two corners, both of them included
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BlockRegion {
+    pub min: BlockPos,
+    pub max: BlockPos,
+}
+
+impl BlockRegion {
+    /// Corners can be given in any order
+    pub fn new(a: BlockPos, b: BlockPos) -> Self {
+        Self {
+            min: BlockPos::new(a.x.min(b.x), a.y.min(b.y), a.z.min(b.z)),
+            max: BlockPos::new(a.x.max(b.x), a.y.max(b.y), a.z.max(b.z)),
+        }
+    }
+
+    /// Region of `size` blocks starting at `min`, every side has to be at least 1 block long
+    pub fn with_size(min: BlockPos, (x, y, z): (usize, usize, usize)) -> Self {
+        Self { min, max: min + BlockPos::new(x as isize - 1, y as isize - 1, z as isize - 1) }
+    }
+
+    /// Blocks along x, y and z, `None` if a side doesn't fit in a `usize`
+    pub fn size(self) -> Option<(usize, usize, usize)> {
+        let side = |min: isize, max: isize| max.checked_sub(min)?.checked_add(1).map(|side| side as usize);
+        Some((side(self.min.x, self.max.x)?, side(self.min.y, self.max.y)?, side(self.min.z, self.max.z)?))
+    }
+
+    /// `None` if the number of blocks doesn't fit in a `usize`
+    pub fn volume(self) -> Option<usize> {
+        let (x, y, z) = self.size()?;
+        x.checked_mul(y)?.checked_mul(z)
+    }
+
+    pub fn contains(self, pos: BlockPos) -> bool {
+        (self.min.x..=self.max.x).contains(&pos.x)
+            && (self.min.y..=self.max.y).contains(&pos.y)
+            && (self.min.z..=self.max.z).contains(&pos.z)
+    }
+
+    /// Block on one of the 6 sides of the region
+    pub fn is_border(self, pos: BlockPos) -> bool {
+        self.contains(pos)
+            && (pos.x == self.min.x
+                || pos.x == self.max.x
+                || pos.y == self.min.y
+                || pos.y == self.max.y
+                || pos.z == self.min.z
+                || pos.z == self.max.z)
+    }
+
+    /// Every block of the region, x changes slowest and z fastest
+    pub fn positions(self) -> impl Iterator<Item = BlockPos> {
+        let BlockRegion { min, max } = self;
+        (min.x..=max.x).flat_map(move |x| (min.y..=max.y).flat_map(move |y| (min.z..=max.z).map(move |z| BlockPos::new(x, y, z))))
+    }
+}
+
+/// Operations over whole regions. Blocks in unloaded chunks are skipped, every operation returns the number of changed blocks.
+///
+/// ```
+/// # use cubic_game::world::*;
+/// let mut world = World::new();
+/// world.insert_chunk(ChunkPos::new(0, 0, 0), Chunk::EMPTY);
+/// world.insert_chunk(ChunkPos::new(1, 0, 0), Chunk::EMPTY);
+/// world.take_dirty();
+///
+/// let region = BlockRegion::new(BlockPos::new(14, 4, 2), BlockPos::new(10, 0, 0));
+/// assert_eq!(region.volume(), Some(75));
+/// assert_eq!(BlockRegion::new(BlockPos::new(isize::MIN, 0, 0), BlockPos::new(isize::MAX, 0, 0)).volume(), None);
+///
+/// assert_eq!(world.hollow(region, BlockState::STONE), 66);
+/// assert_eq!(world.block(BlockPos::new(12, 2, 1)), &BlockState::AIR);
+/// assert_eq!(world.block(BlockPos::new(12, 2, 2)), &BlockState::STONE);
+/// assert_eq!(world.light(BlockPos::new(12, 2, 1)).sky(), 0);
+/// assert_eq!(world.light(BlockPos::new(12, 5, 1)).sky(), 15);
+///
+/// assert_eq!(world.replace(region, BlockType::Stone, BlockState::DIRT), 66);
+/// assert_eq!(world.fill(region, BlockState::DIRT), 9);
+///
+/// // Half of the copy lands in the next chunk
+/// assert_eq!(world.clone_region(region, BlockPos::new(13, 8, 0)), 75);
+/// assert_eq!(world.block(BlockPos::new(17, 12, 2)), &BlockState::DIRT);
+///
+/// let mut dirty = world.take_dirty();
+/// dirty.sort_by_key(|pos| pos.x);
+/// assert_eq!(dirty, [ChunkPos::new(0, 0, 0), ChunkPos::new(1, 0, 0)]);
+/// ```
+impl World {
+    /// Changes many blocks at once. Light is updated and chunks are marked dirty after the whole batch,
+    /// and only blocks at the edge of the changed area schedule updates around them.
+    pub fn set_blocks(&mut self, blocks: impl IntoIterator<Item = (BlockPos, BlockState)>) -> usize {
+        let mut changed = HashSet::new();
+
+        for (pos, state) in blocks {
+            let (x, y, z) = pos.in_chunk();
+            let Some(chunk) = self.chunk_mut(pos.chunk_pos()) else { continue };
+
+            let block = chunk.get_mut(x, y, z);
+            if *block == state {
+                continue;
+            }
+            *block = state;
+            changed.insert(pos);
+        }
+
+        let positions: Vec<BlockPos> = changed.iter().copied().collect();
+        light::update_lights(self, &positions);
+
+        for pos in &changed {
+            self.mark_dirty_around(*pos);
+        }
+
+        for pos in &changed {
+            if Face::ALL.iter().any(|face| !changed.contains(&pos.neighbour(*face))) {
+                self.schedule_ticks_around(*pos);
+            }
+        }
+        changed.len()
+    }
+
+    pub fn fill(&mut self, region: BlockRegion, state: BlockState) -> usize {
+        self.set_blocks(region.positions().map(|pos| (pos, state.clone())))
+    }
+
+    /// Changes blocks of type `from`, whatever their level
+    pub fn replace(&mut self, region: BlockRegion, from: BlockType, to: BlockState) -> usize {
+        let blocks: Vec<BlockPos> = region.positions().filter(|pos| self.block(*pos).block_type == from).collect();
+        self.set_blocks(blocks.into_iter().map(|pos| (pos, to.clone())))
+    }
+
+    /// Copies the region so that its lowest corner ends up at `to`, the copy may overlap the source
+    pub fn clone_region(&mut self, region: BlockRegion, to: BlockPos) -> usize {
+        let offset = BlockPos::new(to.x - region.min.x, to.y - region.min.y, to.z - region.min.z);
+        let blocks: Vec<(BlockPos, BlockState)> =
+            region.positions().map(|pos| (pos + offset, self.block(pos).clone())).collect();
+        self.set_blocks(blocks)
+    }
+
+    /// Walls of `state` around air
+    pub fn hollow(&mut self, region: BlockRegion, state: BlockState) -> usize {
+        self.set_blocks(region.positions().map(|pos| match region.is_border(pos) {
+            true => (pos, state.clone()),
+            false => (pos, BlockState::AIR),
+        }))
+    }
+}
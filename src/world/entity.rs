@@ -0,0 +1,263 @@
+use std::collections::BTreeMap;
+
+use super::*;
+use crate::item::ItemStack;
+
+/// Blocks per tick on every axis, so moving entities never skip a block
+pub const MAX_SPEED: f32 = 1.0;
+
+/// Gap kept between an entity and the blocks it touches
+const EPSILON: f32 = 1e-3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct EntityId(pub u64);
+
+/// Axis aligned box in world coordinates
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl Aabb {
+    pub fn intersects(&self, other: &Aabb) -> bool {
+        self.min.cmplt(other.max).all() && other.min.cmplt(self.max).all()
+    }
+
+    pub fn translated(self, offset: Vec3) -> Aabb {
+        Aabb { min: self.min + offset, max: self.max + offset }
+    }
+}
+
+/// What the entity is, together with the components only this kind has
+#[derive(Debug, Clone, PartialEq)]
+pub enum EntityKind {
+    Player,
+    FallingBlock(BlockState),
+    /// Dropped stack, `age` in ticks
+    Item { stack: ItemStack, age: u32 },
+}
+
+impl EntityKind {
+    /// Width and height of the AABB
+    pub const fn size(&self) -> Vec2 {
+        match self {
+            EntityKind::Player => vec2(0.6, 1.8),
+            EntityKind::FallingBlock(_) => vec2(0.98, 0.98),
+            EntityKind::Item { .. } => vec2(0.25, 0.25),
+        }
+    }
+
+    /// Camera height above [`Entity::pos`]
+    pub const fn eye_height(&self) -> f32 {
+        match self {
+            EntityKind::Player => 1.62,
+            EntityKind::FallingBlock(_) => 0.49,
+            EntityKind::Item { .. } => 0.125,
+        }
+    }
+
+    /// Blocks per tick squared
+    pub const fn gravity(&self) -> f32 {
+        match self {
+            // The player flies
+            EntityKind::Player => 0.0,
+            EntityKind::FallingBlock(_) | EntityKind::Item { .. } => falling_block::GRAVITY,
+        }
+    }
+
+    /// Blocks the entity can't move through
+    pub const fn collides_with(&self, state: &BlockState) -> bool {
+        match self {
+            EntityKind::Player | EntityKind::Item { .. } => state.is_opaque(),
+            // Lands on torches and crops too and breaks
+            EntityKind::FallingBlock(_) => !state.is_empty() && state.fluid().is_none(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entity {
+    pub kind: EntityKind,
+    /// Center of the bottom of the AABB
+    pub pos: Vec3,
+    /// Blocks per tick
+    pub velocity: Vec3,
+    /// Radians, the same as the camera
+    pub yaw: f32,
+    pub pitch: f32,
+    /// Stood on a block after the last move
+    pub on_ground: bool,
+}
+
+impl Entity {
+    pub fn new(kind: EntityKind, pos: Vec3) -> Self {
+        Self { kind, pos, velocity: Vec3::ZERO, yaw: 0.0, pitch: 0.0, on_ground: false }
+    }
+
+    pub fn aabb(&self) -> Aabb {
+        let size = self.kind.size();
+        let half = vec3(size.x / 2.0, 0.0, size.x / 2.0);
+        Aabb { min: self.pos - half, max: self.pos + half + vec3(0.0, size.y, 0.0) }
+    }
+}
+
+/// Every entity in the world by id, ids are never reused
+#[derive(Debug, Default, Clone)]
+pub struct Entities {
+    entities: BTreeMap<EntityId, Entity>,
+    next_id: u64,
+}
+
+impl Entities {
+    pub fn spawn(&mut self, entity: Entity) -> EntityId {
+        let id = EntityId(self.next_id);
+        self.next_id += 1;
+        self.entities.insert(id, entity);
+        id
+    }
+
+    pub fn remove(&mut self, id: EntityId) -> Option<Entity> {
+        self.entities.remove(&id)
+    }
+
+    pub fn get(&self, id: EntityId) -> Option<&Entity> {
+        self.entities.get(&id)
+    }
+
+    pub fn get_mut(&mut self, id: EntityId) -> Option<&mut Entity> {
+        self.entities.get_mut(&id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (EntityId, &Entity)> {
+        self.entities.iter().map(|(id, entity)| (*id, entity))
+    }
+
+    pub fn ids(&self) -> Vec<EntityId> {
+        self.entities.keys().copied().collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+}
+
+/// ```
+/// # use cubic_game::world::*;
+/// # use cubic_game::world::entity::*;
+/// # use macroquad::prelude::*;
+/// let mut world = World::new();
+/// world.entities_mut().spawn(Entity::new(EntityKind::Player, vec3(3.5, 1.0, 3.5)));
+///
+/// assert!(world.is_blocked_by_entity(BlockPos::new(3, 2, 3), &BlockState::STONE));
+/// assert!(!world.is_blocked_by_entity(BlockPos::new(3, 3, 3), &BlockState::STONE));
+/// assert!(!world.is_blocked_by_entity(BlockPos::new(3, 2, 3), &BlockState::TORCH));
+/// ```
+impl World {
+    /// An entity other than a dropped item would end up inside `state` if it was placed at `pos`
+    pub fn is_blocked_by_entity(&self, pos: BlockPos, state: &BlockState) -> bool {
+        let min = vec3(pos.x as f32, pos.y as f32, pos.z as f32);
+        let cell = Aabb { min, max: min + 1.0 };
+
+        self.entities().iter().any(|(_, entity)| {
+            !matches!(entity.kind, EntityKind::Item { .. }) && entity.kind.collides_with(state) && entity.aabb().intersects(&cell)
+        })
+    }
+
+    /// Applies gravity, moves every entity by its velocity and lets its kind react
+    pub fn tick_entities(&mut self) {
+        for id in self.entities().ids() {
+            let Some(mut entity) = self.entities().get(id).cloned() else { continue };
+
+            entity.velocity.y -= entity.kind.gravity();
+            entity.velocity = entity.velocity.clamp(Vec3::splat(-MAX_SPEED), Vec3::splat(MAX_SPEED));
+
+            move_entity(self, &mut entity);
+
+            let alive = match entity.kind {
+                EntityKind::Player => true,
+                EntityKind::FallingBlock(_) => falling_block::update_falling_block(self, &entity),
+                EntityKind::Item { .. } => item_entity::update_item(self, id, &mut entity),
+            };
+
+            match alive {
+                true => *self.entities_mut().get_mut(id).unwrap() = entity,
+                false => drop(self.entities_mut().remove(id)),
+            }
+        }
+    }
+}
+
+/// Moves the entity by its velocity one axis at a time, stopping in front of blocks it collides with.
+/// Unloaded blocks stop everything.
+///
+/// ```
+/// # use cubic_game::world::*;
+/// # use cubic_game::world::entity::*;
+/// # use macroquad::prelude::*;
+/// let mut world = World::new();
+/// world.insert_chunk(ChunkPos::new(0, 0, 0), Chunk::EMPTY);
+/// world.set_block(BlockPos::new(3, 0, 3), BlockState::STONE);
+///
+/// let mut entity = Entity::new(EntityKind::Player, vec3(3.5, 1.5, 3.5));
+/// entity.velocity = vec3(0.0, -1.0, 0.0);
+/// move_entity(&world, &mut entity);
+///
+/// assert!(entity.on_ground);
+/// assert!((entity.pos.y - 1.0).abs() < 0.01);
+/// assert_eq!(entity.velocity, Vec3::ZERO);
+/// ```
+pub fn move_entity(world: &World, entity: &mut Entity) {
+    entity.on_ground = false;
+
+    for axis in [1, 0, 2] {
+        let delta = entity.velocity[axis];
+        if delta == 0.0 {
+            continue;
+        }
+
+        let aabb = entity.aabb();
+        let mut offset = Vec3::ZERO;
+        offset[axis] = delta;
+
+        // Everything between the box now and after the move
+        let swept = Aabb { min: aabb.min.min(aabb.min + offset), max: aabb.max.max(aabb.max + offset) };
+        let cell = |n: f32| n.floor() as isize;
+        let (min, max) = (swept.min + EPSILON, swept.max - EPSILON);
+
+        let mut allowed = delta;
+
+        for x in cell(min.x)..=cell(max.x) {
+            for y in cell(min.y)..=cell(max.y) {
+                for z in cell(min.z)..=cell(max.z) {
+                    let pos = BlockPos::new(x, y, z);
+                    let loaded = world.contains_chunk(pos.chunk_pos());
+                    if loaded && !entity.kind.collides_with(world.block(pos)) {
+                        continue;
+                    }
+
+                    let block_min = [x, y, z][axis] as f32;
+                    allowed = match delta > 0.0 {
+                        true if block_min >= aabb.max[axis] - EPSILON => allowed.min(block_min - aabb.max[axis] - EPSILON),
+                        false if block_min + 1.0 <= aabb.min[axis] + EPSILON => allowed.max(block_min + 1.0 - aabb.min[axis] + EPSILON),
+                        // Already overlapping, the entity may move out of it
+                        _ => allowed,
+                    };
+                }
+            }
+        }
+
+        if allowed != delta {
+            entity.velocity[axis] = 0.0;
+            entity.on_ground |= axis == 1 && delta < 0.0;
+        }
+        entity.pos[axis] += match delta > 0.0 {
+            true => allowed.max(0.0),
+            false => allowed.min(0.0),
+        };
+    }
+}
@@ -0,0 +1,69 @@
+use super::entity::*;
+use super::*;
+use crate::item::*;
+
+/// Blocks per tick squared
+pub const GRAVITY: f32 = 0.04;
+
+impl World {
+    /// Replaces the block with air and makes it fall as an entity
+    pub fn spawn_falling_block(&mut self, pos: BlockPos) -> Option<EntityId> {
+        let state = self.block(pos).clone();
+        if !self.set_block(pos, BlockState::AIR) {
+            return None;
+        }
+
+        let center = vec3(pos.x as f32 + 0.5, pos.y as f32, pos.z as f32 + 0.5);
+        Some(self.entities_mut().spawn(Entity::new(EntityKind::FallingBlock(state), center)))
+    }
+}
+
+/// Places the block back once it has landed or drops it as an item, `false` when the entity is gone.
+///
+/// ```
+/// # use cubic_game::world::*;
+/// let mut world = World::new();
+/// world.insert_chunk(ChunkPos::new(0, 0, 0), Chunk::EMPTY);
+/// world.set_block(BlockPos::new(3, 0, 3), BlockState::TORCH);
+/// world.set_block(BlockPos::new(5, 0, 5), BlockState::STONE);
+///
+/// world.set_block(BlockPos::new(3, 8, 3), BlockState::GRAVEL);
+/// world.set_block(BlockPos::new(5, 8, 5), BlockState::GRAVEL);
+/// world.tick();
+/// world.tick();
+///
+/// assert_eq!(world.entities().len(), 2);
+/// assert_eq!(world.block(BlockPos::new(5, 8, 5)), &BlockState::AIR);
+///
+/// for _ in 0..40 { world.tick(); }
+///
+/// assert_eq!(world.block(BlockPos::new(5, 1, 5)), &BlockState::GRAVEL);
+/// // Gravel breaks on a torch
+/// assert_eq!(world.block(BlockPos::new(3, 1, 3)), &BlockState::AIR);
+/// assert!(world.entities().iter().any(|(_, entity)| matches!(entity.kind, entity::EntityKind::Item { .. })));
+/// ```
+pub fn update_falling_block(world: &mut World, entity: &Entity) -> bool {
+    let EntityKind::FallingBlock(state) = &entity.kind else { return true };
+    if !entity.on_ground {
+        return true;
+    }
+
+    let cell = BlockPos::from_vec3(entity.pos + vec3(0.0, 0.5, 0.0));
+    let below = cell.neighbour(Face::Bottom);
+
+    // Waits in the air until the chunk below is loaded
+    if !world.contains_chunk(below.chunk_pos()) {
+        return true;
+    }
+
+    // Lands on top of solid blocks, anything else breaks it
+    let target = world.block(cell);
+    if world.block(below).is_opaque() && (target.is_empty() || target.fluid().is_some()) {
+        world.set_block(cell, state.clone());
+    } else {
+        for stack in block_drops(state) {
+            world.spawn_item(entity.pos, stack);
+        }
+    }
+    false
+}
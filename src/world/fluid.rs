@@ -0,0 +1,152 @@
+use super::*;
+
+/// Level of a fluid falling down, it spreads like a source when it lands
+pub const FALLING: u8 = 8;
+
+const HORIZONTAL: [Face; 4] = [Face::Px, Face::Nx, Face::Pz, Face::Nz];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fluid {
+    Water,
+    Lava,
+}
+
+impl Fluid {
+    pub const fn block_type(self) -> BlockType {
+        match self {
+            Fluid::Water => BlockType::Water,
+            Fluid::Lava => BlockType::Lava,
+        }
+    }
+
+    pub const fn source(self) -> BlockState {
+        BlockState::new(self.block_type())
+    }
+
+    pub const fn flowing(self, level: u8) -> BlockState {
+        BlockState::with_level(self.block_type(), level)
+    }
+
+    /// Ticks between two steps of the flow
+    pub const fn tick_delay(self) -> u64 {
+        match self {
+            Fluid::Water => 5,
+            Fluid::Lava => 30,
+        }
+    }
+
+    /// How far the fluid flows from a source on flat ground
+    pub const fn max_level(self) -> u8 {
+        match self {
+            Fluid::Water => 7,
+            Fluid::Lava => 3,
+        }
+    }
+}
+
+/// One step of the flow: flowing fluid follows its neighbours, then spreads down or to the sides.
+///
+/// ```
+/// # use cubic_game::world::*;
+/// let mut floor = Chunk::EMPTY;
+/// floor.blocks[0].fill(BlockState::STONE);
+///
+/// let mut world = World::new();
+/// world.insert_chunk(ChunkPos::new(0, 0, 0), floor);
+/// world.set_block(BlockPos::new(8, 5, 8), BlockState::WATER);
+///
+/// for _ in 0..200 { world.tick(); }
+///
+/// assert_eq!(world.block(BlockPos::new(8, 1, 8)).block_type, BlockType::Water);
+/// assert_eq!(world.block(BlockPos::new(10, 1, 8)).level, 2);
+/// assert_eq!(world.block(BlockPos::new(8, 2, 10)), &BlockState::AIR);
+///
+/// // Without the source the water dries up
+/// world.set_block(BlockPos::new(8, 5, 8), BlockState::AIR);
+/// for _ in 0..600 { world.tick(); }
+///
+/// assert_eq!(world.block(BlockPos::new(10, 1, 8)), &BlockState::AIR);
+/// ```
+pub fn update_fluid(world: &mut World, pos: BlockPos) {
+    let mut state = world.block(pos).clone();
+    let Some(fluid) = state.fluid() else { return };
+
+    if state.level != 0 {
+        let new_state = flowing_state(world, pos, fluid).unwrap_or(BlockState::AIR);
+        if new_state != state {
+            world.set_block(pos, new_state.clone());
+        }
+        if new_state.fluid().is_none() {
+            return;
+        }
+        state = new_state;
+    }
+
+    let below = pos.neighbour(Face::Bottom);
+    let below_state = world.block(below).clone();
+
+    if below_state.is_empty() || (below_state.fluid() == Some(fluid) && below_state.level != 0) {
+        if below_state != fluid.flowing(FALLING) {
+            world.set_block(below, fluid.flowing(FALLING));
+        }
+        return;
+    }
+
+    let level = match state.level {
+        0 | FALLING => 1,
+        level => level + 1,
+    };
+    if level > fluid.max_level() {
+        return;
+    }
+
+    for face in HORIZONTAL {
+        let neighbour = pos.neighbour(face);
+        let neighbour_state = world.block(neighbour);
+
+        let farther = neighbour_state.fluid() == Some(fluid)
+            && neighbour_state.level != 0
+            && neighbour_state.level != FALLING
+            && neighbour_state.level > level;
+
+        if neighbour_state.is_empty() || farther {
+            world.set_block(neighbour, fluid.flowing(level));
+        }
+    }
+}
+
+/// State of flowing fluid fed by its neighbours, `None` if nothing feeds it anymore
+fn flowing_state(world: &World, pos: BlockPos, fluid: Fluid) -> Option<BlockState> {
+    if world.block(pos.neighbour(Face::Top)).fluid() == Some(fluid) {
+        return Some(fluid.flowing(FALLING));
+    }
+
+    let mut sources = 0;
+    let mut nearest = None;
+
+    for face in HORIZONTAL {
+        let neighbour = world.block(pos.neighbour(face));
+        if neighbour.fluid() != Some(fluid) {
+            continue;
+        }
+
+        let distance = match neighbour.level {
+            0 => {
+                sources += 1;
+                0
+            }
+            FALLING => 0,
+            level => level,
+        };
+        nearest = Some(nearest.map_or(distance, |nearest: u8| nearest.min(distance)));
+    }
+
+    // Water between two sources on the ground becomes a source itself
+    let below = world.block(pos.neighbour(Face::Bottom));
+    if fluid == Fluid::Water && sources >= 2 && (below.is_opaque() || *below == fluid.source()) {
+        return Some(fluid.source());
+    }
+
+    let level = nearest? + 1;
+    (level <= fluid.max_level()).then(|| fluid.flowing(level))
+}
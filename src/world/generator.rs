@@ -0,0 +1,162 @@
+use super::*;
+
+/// Water fills the hills up to this height
+pub const SEA_LEVEL: usize = 4;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum GeneratorKind {
+    /// Grass and sand hills with lakes
+    #[default]
+    Hills,
+    /// Grass on top of a few layers of dirt and stone
+    Flat,
+}
+
+impl GeneratorKind {
+    pub const ALL: [GeneratorKind; 2] = [GeneratorKind::Hills, GeneratorKind::Flat];
+
+    pub fn next(self) -> Self {
+        let n = GeneratorKind::ALL.iter().position(|kind| *kind == self).unwrap_or(0);
+        GeneratorKind::ALL[(n + 1) % GeneratorKind::ALL.len()]
+    }
+}
+
+impl Display for GeneratorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl FromStr for GeneratorKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        GeneratorKind::ALL
+            .into_iter()
+            .find(|kind| kind.to_string() == s)
+            .ok_or_else(|| format!("unknown generator `{}`", s))
+    }
+}
+
+/// Makes the terrain of new chunks, the same seed always gives the same terrain.
+///
+/// ```
+/// # use cubic_game::world::*;
+/// # use cubic_game::world::generator::*;
+/// let generator = Generator { kind: GeneratorKind::Hills, seed: 42 };
+/// let pos = ChunkPos::new(3, 0, -2);
+///
+/// assert_eq!(generator.chunk(pos), generator.chunk(pos));
+/// assert_ne!(generator.chunk(pos), Generator { seed: 43, ..generator }.chunk(pos));
+///
+/// // Only the bottom layer of chunks has terrain
+/// assert_eq!(generator.chunk(ChunkPos::new(0, 1, 0)), Chunk::EMPTY);
+///
+/// let flat = Generator { kind: GeneratorKind::Flat, seed: 42 }.chunk(pos);
+/// assert_eq!(flat.get(5, 3, 5), &BlockState::GRASS);
+/// assert_eq!(flat.get(5, 4, 5), &BlockState::AIR);
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Generator {
+    pub kind: GeneratorKind,
+    pub seed: u64,
+}
+
+impl Generator {
+    pub fn chunk(&self, pos: ChunkPos) -> Chunk {
+        if pos.y != 0 {
+            return Chunk::EMPTY;
+        }
+
+        let BlockPos { x: left, z: top, .. } = pos.into();
+        let heights: [[usize; CHUNK_SIZE_16]; CHUNK_SIZE_16] =
+            arr_fn(|x| arr_fn(|z| self.height(left + x as isize, top + z as isize)));
+
+        Chunk::from_fn(|x, y, z| self.block(y, heights[x][z]))
+    }
+
+    /// Height of the top block of the column
+    pub fn height(&self, x: isize, z: isize) -> usize {
+        match self.kind {
+            GeneratorKind::Flat => 3,
+            GeneratorKind::Hills => {
+                let (x, z) = (x as f32, z as f32);
+                let noise = value_noise(self.seed, x / 32.0, z / 32.0) * 0.7
+                    + value_noise(self.seed.wrapping_add(1), x / 8.0, z / 8.0) * 0.3;
+                2 + (noise * 11.0) as usize
+            }
+        }
+    }
+
+    fn block(&self, y: usize, height: usize) -> BlockState {
+        let beach = self.kind == GeneratorKind::Hills && height <= SEA_LEVEL + 1;
+
+        match y {
+            y if y > height && y <= SEA_LEVEL && self.kind == GeneratorKind::Hills => BlockState::WATER,
+            y if y > height => BlockState::AIR,
+            y if y + 3 <= height => BlockState::STONE,
+            _ if beach => BlockState::SAND,
+            y if y == height => BlockState::GRASS,
+            _ => BlockState::DIRT,
+        }
+    }
+
+    /// Chunks within render distance of the origin which aren't loaded yet
+    pub fn generate_missing_chunks(&self, world: &mut World, render_distance: u32) {
+        let render_distance = render_distance as isize;
+
+        for x in -render_distance..=render_distance {
+            for z in -render_distance..=render_distance {
+                let pos = ChunkPos::new(x, 0, z);
+                if !world.contains_chunk(pos) {
+                    world.insert_chunk(pos, self.chunk(pos));
+                }
+            }
+        }
+    }
+}
+
+/// Seed typed by the player, numbers are used as they are and any other text is hashed.
+/// `None` if the text is empty, to pick a random seed.
+///
+/// ```
+/// # use cubic_game::world::generator::*;
+/// assert_eq!(seed_from_text("1234"), Some(1234));
+/// assert_eq!(seed_from_text("glacier"), seed_from_text(" glacier "));
+/// assert_eq!(seed_from_text(""), None);
+/// ```
+pub fn seed_from_text(text: &str) -> Option<u64> {
+    let text = text.trim();
+    if text.is_empty() {
+        return None;
+    }
+
+    // FNV-1a
+    let hash = text.bytes().fold(0xcbf29ce484222325_u64, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x100000001b3));
+    Some(text.parse().unwrap_or(hash))
+}
+
+/// Smoothly interpolated random values on a grid with a step of 1, in 0.0..1.0
+fn value_noise(seed: u64, x: f32, z: f32) -> f32 {
+    let (x0, z0) = (x.floor(), z.floor());
+    let (tx, tz) = (smoothstep(x - x0), smoothstep(z - z0));
+    let (x0, z0) = (x0 as i64, z0 as i64);
+
+    let corner = |dx: i64, dz: i64| random_at(seed, x0 + dx, z0 + dz);
+    let top = corner(0, 0) + (corner(1, 0) - corner(0, 0)) * tx;
+    let bottom = corner(0, 1) + (corner(1, 1) - corner(0, 1)) * tx;
+    top + (bottom - top) * tz
+}
+
+fn smoothstep(t: f32) -> f32 {
+    t * t * (3.0 - 2.0 * t)
+}
+
+fn random_at(seed: u64, x: i64, z: i64) -> f32 {
+    // SplitMix64 finalizer
+    let mut hash = seed ^ (x as u64).wrapping_mul(0x9E3779B97F4A7C15) ^ (z as u64).wrapping_mul(0xC2B2AE3D27D4EB4F);
+    hash = (hash ^ (hash >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    hash = (hash ^ (hash >> 27)).wrapping_mul(0x94D049BB133111EB);
+    hash ^= hash >> 31;
+    (hash >> 40) as f32 / (1u64 << 24) as f32
+}
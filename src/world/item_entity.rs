@@ -0,0 +1,85 @@
+use super::entity::*;
+use super::*;
+use crate::item::*;
+
+/// Ticks before a dropped item can be picked up
+pub const PICKUP_DELAY: u32 = 10;
+
+/// Dropped stacks of the same item closer than this merge
+pub const MERGE_DISTANCE: f32 = 1.0;
+
+/// Horizontal speed kept every tick, items slide less on the ground
+const AIR_DRAG: f32 = 0.95;
+const GROUND_FRICTION: f32 = 0.6;
+
+impl World {
+    /// Drops the stack at `pos`, tossed in a random direction
+    pub fn spawn_item(&mut self, pos: Vec3, stack: ItemStack) -> EntityId {
+        let random = |world: &mut World| world.random() as f32 / u32::MAX as f32 - 0.5;
+
+        let mut entity = Entity::new(EntityKind::Item { stack, age: 0 }, pos);
+        entity.velocity = vec3(random(self) * 0.2, 0.2, random(self) * 0.2);
+
+        self.entities_mut().spawn(entity)
+    }
+
+    /// Replaces the block with air and drops its items
+    pub fn break_block(&mut self, pos: BlockPos) {
+        let state = self.block(pos).clone();
+        if !self.set_block(pos, BlockState::AIR) {
+            return;
+        }
+
+        let center = vec3(pos.x as f32 + 0.5, pos.y as f32 + 0.25, pos.z as f32 + 0.5);
+        for stack in block_drops(&state) {
+            self.spawn_item(center, stack);
+        }
+    }
+}
+
+/// Ages the item and merges it into a nearby stack of the same item, `false` once it has merged.
+///
+/// ```
+/// # use cubic_game::item::*;
+/// # use cubic_game::world::*;
+/// # use macroquad::prelude::*;
+/// let mut world = World::new();
+/// world.insert_chunk(ChunkPos::new(0, 0, 0), Chunk::EMPTY);
+/// world.set_block(BlockPos::new(3, 0, 3), BlockState::STONE);
+///
+/// world.break_block(BlockPos::new(3, 0, 3));
+/// world.spawn_item(vec3(3.5, 0.5, 3.5), ItemStack::new(Item::Block(BlockType::Stone), 2));
+///
+/// for _ in 0..20 { world.tick(); }
+///
+/// assert_eq!(world.entities().len(), 1);
+/// ```
+pub fn update_item(world: &mut World, id: EntityId, entity: &mut Entity) -> bool {
+    let EntityKind::Item { stack, age } = &mut entity.kind else { return true };
+    *age += 1;
+
+    let drag = if entity.on_ground { GROUND_FRICTION } else { AIR_DRAG };
+    entity.velocity.x *= drag;
+    entity.velocity.z *= drag;
+
+    let stack = *stack;
+    let pos = entity.pos;
+
+    let other = world.entities().iter().find_map(|(other_id, other)| match other.kind {
+        EntityKind::Item { stack: other_stack, .. }
+            if other_id != id
+                && other_stack.item == stack.item
+                && other_stack.count + stack.count <= stack.item.max_stack_size()
+                && other.pos.distance(pos) < MERGE_DISTANCE =>
+        {
+            Some(other_id)
+        }
+        _ => None,
+    });
+
+    let Some(other) = other.and_then(|other| world.entities_mut().get_mut(other)) else { return true };
+    if let EntityKind::Item { stack: other_stack, .. } = &mut other.kind {
+        other_stack.count += stack.count;
+    }
+    false
+}
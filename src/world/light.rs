@@ -0,0 +1,382 @@
+use std::collections::VecDeque;
+
+use super::*;
+
+pub const MAX_LIGHT: u8 = 15;
+
+const N_BLOCKS: usize = CHUNK_SIZE_16 * CHUNK_SIZE_16 * CHUNK_SIZE_16;
+
+/// Sky light and block light levels 0..=15 packed into one byte
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Light(u8);
+
+impl Light {
+    pub const DARK: Light = Light(0);
+    pub const SKY: Light = Light::new(MAX_LIGHT, 0);
+
+    pub const fn new(sky: u8, block: u8) -> Self {
+        Self((sky << 4) | (block & 0xF))
+    }
+
+    pub const fn sky(self) -> u8 {
+        self.0 >> 4
+    }
+
+    pub const fn block(self) -> u8 {
+        self.0 & 0xF
+    }
+
+    pub const fn get(self, channel: LightChannel) -> u8 {
+        match channel {
+            LightChannel::Sky => self.sky(),
+            LightChannel::Block => self.block(),
+        }
+    }
+
+    pub fn set(&mut self, channel: LightChannel, level: u8) {
+        *self = match channel {
+            LightChannel::Sky => Light::new(level, self.block()),
+            LightChannel::Block => Light::new(self.sky(), level),
+        }
+    }
+
+    /// 0.0..=1.0, brighter of the two channels
+    pub fn brightness(self) -> f32 {
+        self.sky_brightness().max(self.block_brightness())
+    }
+
+    pub fn sky_brightness(self) -> f32 {
+        level_brightness(self.sky())
+    }
+
+    pub fn block_brightness(self) -> f32 {
+        level_brightness(self.block())
+    }
+}
+
+/// 0.0..=1.0, every level is 20% darker than the previous one and level 0 is black
+pub fn level_brightness(level: u8) -> f32 {
+    match level {
+        0 => 0.0,
+        level => 0.8f32.powi((MAX_LIGHT - level.min(MAX_LIGHT)) as i32),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LightChannel {
+    Sky,
+    Block,
+}
+
+impl LightChannel {
+    pub const ALL: [LightChannel; 2] = [LightChannel::Sky, LightChannel::Block];
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChunkLight([Light; N_BLOCKS]);
+
+impl ChunkLight {
+    pub const DARK: ChunkLight = ChunkLight([Light::DARK; N_BLOCKS]);
+
+    /// (usize, usize, usize) - pos in chunk 0..16
+    pub fn get(&self, x: usize, y: usize, z: usize) -> Light {
+        self.0[(y * CHUNK_SIZE_16 + x) * CHUNK_SIZE_16 + z]
+    }
+
+    pub fn get_mut(&mut self, x: usize, y: usize, z: usize) -> &mut Light {
+        &mut self.0[(y * CHUNK_SIZE_16 + x) * CHUNK_SIZE_16 + z]
+    }
+}
+
+impl Default for ChunkLight {
+    fn default() -> Self {
+        Self::DARK
+    }
+}
+
+/// 16x16 slice of light levels, indexed the same way as [`ChunkLayer`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LightLayer(pub [[Light; CHUNK_SIZE_16]; CHUNK_SIZE_16]);
+
+impl LightLayer {
+    /// Light of unloaded chunks
+    pub const SKY: LightLayer = LightLayer([[Light::SKY; CHUNK_SIZE_16]; CHUNK_SIZE_16]);
+
+    pub fn from_fn(mut func: impl FnMut(usize, usize) -> Light) -> Self {
+        Self(arr_fn(|a| arr_fn(|b| func(a, b))))
+    }
+
+    pub fn get(&self, a: usize, b: usize) -> Light {
+        self.0[a][b]
+    }
+}
+
+impl World {
+    /// Unloaded blocks are lit by the sky
+    pub fn light(&self, pos: BlockPos) -> Light {
+        self.loaded_light(pos).unwrap_or(Light::SKY)
+    }
+
+    fn loaded_light(&self, pos: BlockPos) -> Option<Light> {
+        let (x, y, z) = pos.in_chunk();
+        self.chunk(pos.chunk_pos()).map(|chunk| chunk.light.get(x, y, z))
+    }
+
+    /// Marks chunks which show this light dirty
+    fn set_light(&mut self, pos: BlockPos, channel: LightChannel, level: u8) {
+        let chunk_pos = pos.chunk_pos();
+        let (x, y, z) = pos.in_chunk();
+
+        let Some(chunk) = self.chunk_mut(chunk_pos) else { return };
+        chunk.light.get_mut(x, y, z).set(channel, level);
+
+        self.mark_dirty_around(pos);
+    }
+}
+
+/// Lights a newly inserted chunk from the sky, its light sources and its loaded neighbours,
+/// spreading the light into the neighbours as well.
+///
+/// ```
+/// # use cubic_game::world::*;
+/// let mut cave = Chunk::EMPTY;
+/// cave.blocks[15].fill(BlockState::STONE);
+///
+/// let mut world = World::new();
+/// world.insert_chunk(ChunkPos::new(0, 0, 0), cave);
+/// assert_eq!(world.light(BlockPos::new(8, 8, 8)).sky(), 0);
+///
+/// world.set_block(BlockPos::new(8, 8, 8), BlockState::TORCH);
+/// assert_eq!(world.light(BlockPos::new(8, 8, 10)).block(), 12);
+///
+/// // Hole in the roof
+/// world.set_block(BlockPos::new(8, 15, 8), BlockState::AIR);
+/// assert_eq!(world.light(BlockPos::new(8, 0, 8)).sky(), 15);
+/// assert_eq!(world.light(BlockPos::new(9, 0, 8)).sky(), 14);
+/// ```
+pub fn light_chunk(world: &mut World, pos: ChunkPos) {
+    let origin: BlockPos = pos.into();
+    let last = CHUNK_SIZE_16 - 1;
+
+    let sky_above = LightLayer::from_fn(|x, z| {
+        world.light(origin + BlockPos::new(x as isize, CHUNK_SIZE_16 as isize, z as isize))
+    });
+
+    let Some(chunk) = world.chunk_mut(pos) else { return };
+    chunk.light = ChunkLight::DARK;
+
+    // Sky light falls down without getting darker
+    for x in 0..CHUNK_SIZE_16 {
+        for z in 0..CHUNK_SIZE_16 {
+            if sky_above.get(x, z).sky() != MAX_LIGHT {
+                continue;
+            }
+            for y in (0..CHUNK_SIZE_16).rev() {
+                if chunk.get(x, y, z).is_opaque() {
+                    break;
+                }
+                chunk.light.get_mut(x, y, z).set(LightChannel::Sky, MAX_LIGHT);
+            }
+        }
+    }
+
+    let mut sky_queue = VecDeque::new();
+    let mut block_queue = VecDeque::new();
+
+    for y in 0..CHUNK_SIZE_16 {
+        for x in 0..CHUNK_SIZE_16 {
+            for z in 0..CHUNK_SIZE_16 {
+                let block_pos = origin + BlockPos::new(x as isize, y as isize, z as isize);
+
+                let emission = chunk.get(x, y, z).light_emission();
+                if emission > 0 {
+                    chunk.light.get_mut(x, y, z).set(LightChannel::Block, emission);
+                    block_queue.push_back(block_pos);
+                }
+
+                if chunk.light.get(x, y, z).sky() != MAX_LIGHT {
+                    continue;
+                }
+
+                // Only cells next to darker cells can spread the light further, borders are checked below
+                let next_to_darker = [(x + 1, z), (x.wrapping_sub(1), z), (x, z + 1), (x, z.wrapping_sub(1))]
+                    .into_iter()
+                    .filter(|(x, z)| *x < CHUNK_SIZE_16 && *z < CHUNK_SIZE_16)
+                    .any(|(x, z)| !chunk.get(x, y, z).is_opaque() && chunk.light.get(x, y, z).sky() < MAX_LIGHT);
+
+                if next_to_darker {
+                    sky_queue.push_back(block_pos);
+                }
+            }
+        }
+    }
+
+    world.mark_dirty(pos);
+
+    // Light crossing the borders in either direction
+    let chunk = world.chunk(pos).unwrap();
+
+    for face in Face::ALL {
+        let Some(neighbour_chunk) = world.chunk(pos.neighbour(face)) else { continue };
+
+        for a in 0..CHUNK_SIZE_16 {
+            for b in 0..CHUNK_SIZE_16 {
+                #[rustfmt::skip]
+                let ((x, y, z), (nx, ny, nz)) = match face {
+                    Face::Top => ((a, last, b), (a, 0, b)), Face::Bottom => ((a, 0, b), (a, last, b)),
+                    Face::Px => ((last, a, b), (0, a, b)), Face::Nx => ((0, a, b), (last, a, b)),
+                    Face::Pz => ((a, b, last), (a, b, 0)), Face::Nz => ((a, b, 0), (a, b, last)),
+                };
+                let block_pos = origin + BlockPos::new(x as isize, y as isize, z as isize);
+
+                let here = chunk.light.get(x, y, z);
+                let there = neighbour_chunk.light.get(nx, ny, nz);
+
+                for (channel, queue) in [(LightChannel::Sky, &mut sky_queue), (LightChannel::Block, &mut block_queue)] {
+                    let (here, there) = (here.get(channel), there.get(channel));
+
+                    if !neighbour_chunk.get(nx, ny, nz).is_opaque() && spread(channel, face, here) > there {
+                        queue.push_back(block_pos);
+                    }
+                    if !chunk.get(x, y, z).is_opaque() && spread(channel, face.opposite(), there) > here {
+                        queue.push_back(block_pos.neighbour(face));
+                    }
+                }
+            }
+        }
+    }
+
+    propagate(world, LightChannel::Sky, sky_queue);
+    propagate(world, LightChannel::Block, block_queue);
+
+    // Chunk below was lit by the open sky, which this chunk may cover now
+    if world.contains_chunk(pos.neighbour(Face::Bottom)) {
+        for x in 0..CHUNK_SIZE_16 {
+            for z in 0..CHUNK_SIZE_16 {
+                let bottom = origin + BlockPos::new(x as isize, 0, z as isize);
+                let below = bottom.neighbour(Face::Bottom);
+
+                if world.light(below).sky() == MAX_LIGHT && world.light(bottom).sky() != MAX_LIGHT {
+                    let seeds = remove(world, LightChannel::Sky, below);
+                    propagate(world, LightChannel::Sky, seeds.into());
+                }
+            }
+        }
+    }
+}
+
+/// Updates light around a block which has just changed
+pub fn update_light(world: &mut World, pos: BlockPos) {
+    update_lights(world, &[pos]);
+}
+
+/// Updates light around many changed blocks, removing the old light of all of them before spreading the new light once
+pub fn update_lights(world: &mut World, positions: &[BlockPos]) {
+    for channel in LightChannel::ALL {
+        let mut seeds = Vec::new();
+
+        for &pos in positions {
+            seeds.extend(remove(world, channel, pos));
+        }
+
+        for &pos in positions {
+            let state = world.block(pos).clone();
+
+            if !state.is_opaque() {
+                // Neighbours light the block again
+                seeds.extend(Face::ALL.map(|face| pos.neighbour(face)));
+
+                let above = pos.neighbour(Face::Top);
+                if channel == LightChannel::Sky && !world.contains_chunk(above.chunk_pos()) {
+                    world.set_light(pos, channel, MAX_LIGHT);
+                    seeds.push(pos);
+                }
+            }
+
+            if channel == LightChannel::Block && state.light_emission() > 0 {
+                world.set_light(pos, channel, state.light_emission());
+                seeds.push(pos);
+            }
+        }
+
+        propagate(world, channel, seeds.into());
+    }
+}
+
+/// Spreads light from every position in the queue to its darker neighbours
+fn propagate(world: &mut World, channel: LightChannel, mut queue: VecDeque<BlockPos>) {
+    while let Some(pos) = queue.pop_front() {
+        let Some(light) = world.loaded_light(pos) else { continue };
+        let level = light.get(channel);
+
+        if level <= 1 {
+            continue;
+        }
+
+        for face in Face::ALL {
+            let neighbour = pos.neighbour(face);
+            let Some(neighbour_light) = world.loaded_light(neighbour) else { continue };
+
+            if world.block(neighbour).is_opaque() {
+                continue;
+            }
+
+            let new_level = spread(channel, face, level);
+
+            if neighbour_light.get(channel) < new_level {
+                world.set_light(neighbour, channel, new_level);
+                queue.push_back(neighbour);
+            }
+        }
+    }
+}
+
+/// Level of light after moving one block towards `face`
+fn spread(channel: LightChannel, face: Face, level: u8) -> u8 {
+    match channel == LightChannel::Sky && face == Face::Bottom && level == MAX_LIGHT {
+        true => MAX_LIGHT,
+        false => level.saturating_sub(1),
+    }
+}
+
+/// Removes light which came through `pos`. Returns lit positions at the edge of the removed area,
+/// which have to be propagated again.
+fn remove(world: &mut World, channel: LightChannel, pos: BlockPos) -> Vec<BlockPos> {
+    let Some(light) = world.loaded_light(pos) else { return Vec::new() };
+
+    let mut seeds = Vec::new();
+    let mut queue = VecDeque::from([(pos, light.get(channel))]);
+
+    world.set_light(pos, channel, 0);
+
+    while let Some((pos, level)) = queue.pop_front() {
+        for face in Face::ALL {
+            let neighbour = pos.neighbour(face);
+            let Some(neighbour_light) = world.loaded_light(neighbour) else { continue };
+            let neighbour_level = neighbour_light.get(channel);
+
+            if neighbour_level == 0 {
+                continue;
+            }
+
+            let sky_column = channel == LightChannel::Sky
+                && face == Face::Bottom
+                && level == MAX_LIGHT
+                && neighbour_level == MAX_LIGHT;
+
+            if neighbour_level < level || sky_column {
+                world.set_light(neighbour, channel, 0);
+                queue.push_back((neighbour, neighbour_level));
+
+                let emission = world.block(neighbour).light_emission();
+                if channel == LightChannel::Block && emission > 0 {
+                    world.set_light(neighbour, channel, emission);
+                    seeds.push(neighbour);
+                }
+            } else {
+                seeds.push(neighbour);
+            }
+        }
+    }
+    seeds
+}
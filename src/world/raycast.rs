@@ -0,0 +1,76 @@
+use super::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RayHit {
+    pub pos: BlockPos,
+    /// Face of the hit block the ray entered through, `None` if the ray started inside the block
+    pub face: Option<Face>,
+}
+
+/// First non-empty block along the ray, stepping from block to block.
+///
+/// ```
+/// # use cubic_game::world::*;
+/// # use macroquad::prelude::*;
+/// let mut world = World::new();
+/// world.insert_chunk(ChunkPos::new(0, 0, 0), Chunk::EMPTY);
+/// world.set_block(BlockPos::new(5, 2, 2), BlockState::STONE);
+///
+/// let hit = raycast(&world, vec3(0.5, 2.5, 2.5), Vec3::X, 10.0).unwrap();
+/// assert_eq!(hit.pos, BlockPos::new(5, 2, 2));
+/// assert_eq!(hit.face, Some(Face::Nx));
+///
+/// assert_eq!(raycast(&world, vec3(0.5, 2.5, 2.5), Vec3::X, 3.0), None);
+/// ```
+pub fn raycast(world: &World, origin: Vec3, direction: Vec3, max_distance: f32) -> Option<RayHit> {
+    let direction = direction.try_normalize()?;
+
+    let mut pos = BlockPos::from_vec3(origin);
+    let mut face = None;
+
+    let step = |d: f32| if d > 0.0 { 1 } else { -1 };
+    let (step_x, step_y, step_z) = (step(direction.x), step(direction.y), step(direction.z));
+
+    // Distance along the ray to cross one block on each axis
+    let delta = (1.0 / direction).abs();
+
+    // Distance along the ray to the next block boundary on each axis
+    let boundary = |p: f32, d: f32, delta: f32| match d {
+        0.0 => f32::INFINITY,
+        d if d > 0.0 => (p.floor() + 1.0 - p) * delta,
+        _ => (p - p.floor()) * delta,
+    };
+    let mut next = vec3(
+        boundary(origin.x, direction.x, delta.x),
+        boundary(origin.y, direction.y, delta.y),
+        boundary(origin.z, direction.z, delta.z),
+    );
+
+    loop {
+        if !world.block(pos).is_empty() {
+            return Some(RayHit { pos, face });
+        }
+
+        let distance;
+        if next.x < next.y && next.x < next.z {
+            pos.x += step_x;
+            distance = next.x;
+            next.x += delta.x;
+            face = Some(if step_x > 0 { Face::Nx } else { Face::Px });
+        } else if next.y < next.z {
+            pos.y += step_y;
+            distance = next.y;
+            next.y += delta.y;
+            face = Some(if step_y > 0 { Face::Bottom } else { Face::Top });
+        } else {
+            pos.z += step_z;
+            distance = next.z;
+            next.z += delta.z;
+            face = Some(if step_z > 0 { Face::Nz } else { Face::Pz });
+        }
+
+        if distance > max_distance {
+            return None;
+        }
+    }
+}
@@ -1,17 +1,36 @@
 use std::fmt::Debug;
-use std::ops::Add;
 
 use derive_more::{Deref, DerefMut};
 use macroquad::prelude::*;
 
+use super::light::*;
 use super::*;
+use crate::item::Item;
 
 mod build_chunk_mesh;
-pub use build_chunk_mesh::build_chunk_meshes;
+pub use build_chunk_mesh::{build_chunk_mesh, build_chunk_meshes, build_entities_mesh, build_fluid_mesh, build_lod_mesh};
 
 mod build_chunk_model;
 pub use build_chunk_model::build_chunk_model;
 
+mod build_lod_model;
+pub use build_lod_model::{build_lod_model, Lod};
+
+mod frustum;
+pub use frustum::Frustum;
+
+mod chunk_material;
+pub use chunk_material::*;
+
+mod chunk_meshes;
+pub use chunk_meshes::{ChunkMeshes, DrawStats, MeshStats, MESHES_PER_FRAME};
+
+mod visibility;
+pub use visibility::{ChunkVisibility, VisibilityGraph};
+
+mod sky;
+pub use sky::Sky;
+
 #[derive(Default, Clone, PartialEq)]
 pub struct ChunkModel(Option<[ModelLayer; CHUNK_SIZE_16]>);
 
@@ -65,55 +84,50 @@ impl ModelLayer {
     }
 }
 
-struct ChunkPlusConnected<'ch, 'to, 'bo, 'px, 'nx, 'pz, 'nz> {
+struct ChunkPlusConnected<'ch, 'co> {
     chunk: &'ch Chunk,
-    top: &'to ChunkLayer,
-    bottom: &'bo ChunkLayer,
-    px: &'px ChunkLayer,
-    nx: &'nx ChunkLayer,
-    pz: &'pz ChunkLayer,
-    nz: &'nz ChunkLayer,
+    conn: &'co ConnectedChunks,
 }
 
 #[rustfmt::skip]
-impl<'ch, 'to, 'bo, 'px, 'nx, 'pz, 'nz> ChunkPlusConnected<'ch, 'to, 'bo, 'px, 'nx, 'pz, 'nz> {
+impl<'ch, 'co> ChunkPlusConnected<'ch, 'co> {
     /// (usize, usize, usize) - (x, y, z) pos in chunk 0..16
     #[rustfmt::skip]
-    fn connected_blocks(&self, x: usize, y: usize, z: usize) -> ConnectedBlocks {
+    fn connected_blocks(&self, x: usize, y: usize, z: usize) -> ConnectedBlocks<'_, '_, '_, '_, '_, '_> {
 
-        let top = if y == 15 { &self.top.get(x, z) } else { &self.chunk.get(x, y + 1, z) };
-        let bottom = if y == 0 { &self.bottom.get(x, z) } else { &self.chunk.get(x, y - 1, z) };
+        let top = if y == 15 { self.conn.top.get(x, z) } else { self.chunk.get(x, y + 1, z) };
+        let bottom = if y == 0 { self.conn.bottom.get(x, z) } else { self.chunk.get(x, y - 1, z) };
 
-        let px = if x == 15 { &self.px.get(x, z) } else { &self.chunk.get(x + 1, y, z) };
-        let nx = if x == 0 { &self.nx.get(x, z) } else { &self.chunk.get(x - 1, y, z) };
+        let px = if x == 15 { self.conn.px.get(y, z) } else { self.chunk.get(x + 1, y, z) };
+        let nx = if x == 0 { self.conn.nx.get(y, z) } else { self.chunk.get(x - 1, y, z) };
 
-        let pz = if z == 15 { &self.pz.get(x, z) } else { &self.chunk.get(x, y, z + 1) };
-        let nz = if z == 0 { &self.nz.get(x, z) } else { &self.chunk.get(x, y, z - 1) };
+        let pz = if z == 15 { self.conn.pz.get(x, y) } else { self.chunk.get(x, y, z + 1) };
+        let nz = if z == 0 { self.conn.nz.get(x, y) } else { self.chunk.get(x, y, z - 1) };
 
-        ConnectedBlocks::new(&top, &bottom, &px, &nx, &pz, &nz)
+        ConnectedBlocks::new(top, bottom, px, nx, pz, nz)
     }
-}
-
-#[derive(Clone, Copy)]
-pub struct ChunkPos {
-    pub x: isize,
-    pub y: isize,
-    pub z: isize,
-}
-
-impl ChunkPos {
-    pub fn new(x: isize, y: isize, z: isize) -> Self {
-        Self { x, y, z }
-    }
-}
 
-impl Into<BlockPos> for ChunkPos {
-    fn into(self) -> BlockPos {
-        let ChunkPos { x, y, z } = self;
-        BlockPos {
-            x: x * CHUNK_SIZE_16 as isize,
-            y: y * CHUNK_SIZE_16 as isize,
-            z: z * CHUNK_SIZE_16 as isize,
+    /// (isize, isize, isize) - (x, y, z) pos in chunk -1..=16, blocks outside come from the neighbouring chunks
+    #[rustfmt::skip]
+    fn neighbourhood(&self, x: isize, y: isize, z: isize) -> (&BlockState, Light) {
+        let size = CHUNK_SIZE_16 as isize;
+        let outside = |n: isize| n < 0 || n >= size;
+
+        let (conn, light) = (self.conn, &self.conn.light);
+        let (ux, uy, uz) = (x as usize, y as usize, z as usize);
+
+        match (outside(x), outside(y), outside(z)) {
+            (false, false, false) => (self.chunk.get(ux, uy, uz), self.chunk.light.get(ux, uy, uz)),
+            (false, true, false) if y >= size => (conn.top.get(ux, uz), light.top.get(ux, uz)),
+            (false, true, false) => (conn.bottom.get(ux, uz), light.bottom.get(ux, uz)),
+            (true, false, false) if x >= size => (conn.px.get(uy, uz), light.px.get(uy, uz)),
+            (true, false, false) => (conn.nx.get(uy, uz), light.nx.get(uy, uz)),
+            (false, false, true) if z >= size => (conn.pz.get(ux, uy), light.pz.get(ux, uy)),
+            (false, false, true) => (conn.nz.get(ux, uy), light.nz.get(ux, uy)),
+            _ => {
+                let (state, light) = conn.edges.get(x, y, z);
+                (state, *light)
+            }
         }
     }
 }
@@ -150,6 +164,16 @@ impl UvTexture {
     pub const GRASS_TOP: UvTexture = UvTexture::from_n(2);
     pub const STONE: UvTexture = UvTexture::from_n(3);
     pub const SAND: UvTexture = UvTexture::from_n(4);
+    pub const TORCH: UvTexture = UvTexture::from_n(5);
+    pub const WATER: UvTexture = UvTexture::from_n(6);
+    pub const LAVA: UvTexture = UvTexture::from_n(7);
+    pub const WHEAT: UvTexture = UvTexture::from_n(8);
+    pub const GRAVEL: UvTexture = UvTexture::from_n(9);
+    pub const PLAYER: UvTexture = UvTexture::from_n(10);
+    pub const STICK: UvTexture = UvTexture::from_n(11);
+    pub const WHEAT_ITEM: UvTexture = UvTexture::from_n(12);
+    pub const SEEDS: UvTexture = UvTexture::from_n(13);
+    pub const CRAFTING_TABLE: UvTexture = UvTexture::from_n(14);
 }
 
 impl Debug for UvTexture {
@@ -170,32 +194,47 @@ use macroquad::models::Vertex;
 
 const WHITE: [u8; 4] = [u8::MAX, u8::MAX, u8::MAX, u8::MAX];
 
-const fn vertex(pos: Vec3, uv: Vec2) -> Vertex {
-    Vertex {
-        normal: Vec4::ONE,
-        position: pos,
-        uv,
-        color: WHITE,
-    }
+/// Vertex color of a block lit by `light`, see [`encode_light`]
+fn light_color(light: Light, face: Face) -> [u8; 4] {
+    let shade = face_shade(face);
+    encode_light(light.sky_brightness() * shade, light.block_brightness() * shade)
 }
 
-#[derive(Debug, Clone, Copy)]
-pub struct BlockPos {
-    pub x: isize,
-    pub y: isize,
-    pub z: isize,
+/// Red is sky light and green is block light, so the chunk shader can tint them separately
+fn encode_light(sky: f32, block: f32) -> [u8; 4] {
+    let byte = |value: f32| (value.clamp(0.0, 1.0) * u8::MAX as f32) as u8;
+    [byte(sky), byte(block), u8::MAX, u8::MAX]
 }
 
-impl Add for BlockPos {
-    type Output = BlockPos;
+/// Faces facing away from the sun are darker, so the shape of the terrain is visible even in flat light
+///
+/// ```
+/// # use cubic_game::world::Face;
+/// # use cubic_game::world::render::face_shade;
+/// assert!(face_shade(Face::Top) > face_shade(Face::Px));
+/// assert!(face_shade(Face::Nz) > face_shade(Face::Bottom));
+/// ```
+pub const fn face_shade(face: Face) -> f32 {
+    match face {
+        Face::Top => 1.0,
+        Face::Bottom => 0.5,
+        Face::Px | Face::Nx => 0.8,
+        Face::Pz | Face::Nz => 0.65,
+    }
+}
 
-    fn add(self, rhs: Self) -> Self::Output {
-        let BlockPos { x, y, z } = self;
-        BlockPos {
-            x: x + rhs.x,
-            y: y + rhs.y,
-            z: z + rhs.z,
-        }
+/// Unit vector pointing out of the face, `w` is 0
+pub const fn face_normal(face: Face) -> Vec4 {
+    let (x, y, z) = face.offset();
+    vec4(x as f32, y as f32, z as f32, 0.)
+}
+
+const fn vertex(pos: Vec3, uv: Vec2, normal: Vec4) -> Vertex {
+    Vertex {
+        normal,
+        position: pos,
+        uv,
+        color: WHITE,
     }
 }
 
@@ -211,7 +250,7 @@ pub struct ConnectedBlocks<'to, 'bo, 'px, 'nx, 'pz, 'nz> {
 #[rustfmt::skip]
 impl<'to, 'bo, 'px, 'nx, 'pz, 'nz> ConnectedBlocks<'to, 'bo, 'px, 'nx, 'pz, 'nz> {
 
-    const EMPTY: ConnectedBlocks<'static, 'static, 'static, 'static, 'static, 'static> 
+    pub const EMPTY: ConnectedBlocks<'static, 'static, 'static, 'static, 'static, 'static> 
                 = ConnectedBlocks::new(
                     &BlockState::EMPTY, &BlockState::EMPTY, &BlockState::EMPTY,
                     &BlockState::EMPTY, &BlockState::EMPTY, &BlockState::EMPTY,
@@ -226,33 +265,115 @@ impl<'to, 'bo, 'px, 'nx, 'pz, 'nz> ConnectedBlocks<'to, 'bo, 'px, 'nx, 'pz, 'nz>
     }
 }
 
-pub struct ConnectedChunks<'to, 'bo, 'px, 'nx, 'pz, 'nz> {
-    pub top: &'to ChunkLayer,
-    pub bottom: &'bo ChunkLayer,
-    pub px: &'px ChunkLayer,
-    pub nx: &'nx ChunkLayer,
-    pub pz: &'pz ChunkLayer,
-    pub nz: &'nz ChunkLayer,
+/// Border layers of the 6 neighbouring chunks which touch this chunk.
+///
+/// `top` and `bottom` are indexed by (x, z), `px` and `nx` by (y, z), `pz` and `nz` by (x, y).
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConnectedChunks {
+    pub top: ChunkLayer,
+    pub bottom: ChunkLayer,
+    pub px: ChunkLayer,
+    pub nx: ChunkLayer,
+    pub pz: ChunkLayer,
+    pub nz: ChunkLayer,
+    pub light: ConnectedLight,
+    pub edges: ConnectedEdges,
 }
 
-#[rustfmt::skip]
-impl<'to, 'bo, 'px, 'nx, 'pz, 'nz> ConnectedChunks<'to, 'bo, 'px, 'nx, 'pz, 'nz> {
+impl ConnectedChunks {
+    pub const EMPTY: ConnectedChunks = ConnectedChunks {
+        top: ChunkLayer::EMPTY,
+        bottom: ChunkLayer::EMPTY,
+        px: ChunkLayer::EMPTY,
+        nx: ChunkLayer::EMPTY,
+        pz: ChunkLayer::EMPTY,
+        nz: ChunkLayer::EMPTY,
+        light: ConnectedLight::SKY,
+        edges: ConnectedEdges::EMPTY,
+    };
+}
 
-    pub const EMPTY: ConnectedChunks<'static, 'static, 'static, 'static, 'static, 'static> 
-            = ConnectedChunks::new(
-                &ChunkLayer::EMPTY, &ChunkLayer::EMPTY, &ChunkLayer::EMPTY,
-                &ChunkLayer::EMPTY, &ChunkLayer::EMPTY, &ChunkLayer::EMPTY,
-            );
+/// Light of the border layers in [`ConnectedChunks`], indexed the same way
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConnectedLight {
+    pub top: LightLayer,
+    pub bottom: LightLayer,
+    pub px: LightLayer,
+    pub nx: LightLayer,
+    pub pz: LightLayer,
+    pub nz: LightLayer,
+}
 
-    pub const fn new(
-        top: &'to ChunkLayer, bottom: &'bo ChunkLayer, 
-        px: &'px ChunkLayer, nx: &'nx ChunkLayer, 
-        pz: &'pz ChunkLayer, nz: &'nz ChunkLayer,
-    ) -> Self {
-        Self { top, bottom, px, nx, pz, nz }
+impl ConnectedLight {
+    pub const SKY: ConnectedLight = ConnectedLight {
+        top: LightLayer::SKY,
+        bottom: LightLayer::SKY,
+        px: LightLayer::SKY,
+        nx: LightLayer::SKY,
+        pz: LightLayer::SKY,
+        nz: LightLayer::SKY,
+    };
+}
+
+const AIR_IN_SKY: (BlockState, Light) = (BlockState::AIR, Light::SKY);
+const EMPTY_EDGE: [(BlockState, Light); CHUNK_SIZE_16] = [AIR_IN_SKY; CHUNK_SIZE_16];
+
+/// Blocks of the diagonal neighbours along the 12 edges and at the 8 corners of the chunk.
+///
+/// Edges are grouped by the axis they run along (x, y, z), and inside each group
+/// by which side of the chunk the other two coordinates are on.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConnectedEdges {
+    pub edges: [[(BlockState, Light); CHUNK_SIZE_16]; 12],
+    pub corners: [(BlockState, Light); 8],
+}
+
+impl ConnectedEdges {
+    pub const EMPTY: ConnectedEdges = ConnectedEdges {
+        edges: [EMPTY_EDGE; 12],
+        corners: [AIR_IN_SKY; 8],
+    };
+
+    /// (isize, isize, isize) - (x, y, z) pos relative to the chunk, at least two coordinates are -1 or 16
+    pub fn from_fn(mut func: impl FnMut(isize, isize, isize) -> (BlockState, Light)) -> Self {
+        let mut edges = ConnectedEdges::EMPTY;
+        let side = |bit: usize| if bit == 0 { -1 } else { CHUNK_SIZE_16 as isize };
+
+        for (n, edge) in edges.edges.iter_mut().enumerate() {
+            let (a, b) = (side(n & 2), side(n & 1));
+            for (i, block) in edge.iter_mut().enumerate() {
+                let i = i as isize;
+                *block = match n / 4 {
+                    0 => func(i, a, b),
+                    1 => func(a, i, b),
+                    _ => func(a, b, i),
+                };
+            }
+        }
+        for (n, corner) in edges.corners.iter_mut().enumerate() {
+            *corner = func(side(n & 4), side(n & 2), side(n & 1));
+        }
+        edges
+    }
+
+    /// (isize, isize, isize) - (x, y, z) pos relative to the chunk, at least two coordinates are -1 or 16
+    #[rustfmt::skip]
+    pub fn get(&self, x: isize, y: isize, z: isize) -> &(BlockState, Light) {
+        let size = CHUNK_SIZE_16 as isize;
+        let side = |n: isize| if n < 0 { 0 } else { 1 };
+        let outside = |n: isize| n < 0 || n >= size;
+
+        match (outside(x), outside(y), outside(z)) {
+            (true, true, true) => &self.corners[side(x) * 4 + side(y) * 2 + side(z)],
+            (false, true, true) => &self.edges[side(y) * 2 + side(z)][x as usize],
+            (true, false, true) => &self.edges[4 + side(x) * 2 + side(z)][y as usize],
+            (true, true, false) => &self.edges[8 + side(x) * 2 + side(y)][z as usize],
+            _ => panic!("({}, {}, {}) is not on an edge of the chunk", x, y, z),
+        }
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum MyTexture {
     Transparent,
     AllSides(UvTexture),
@@ -264,7 +385,7 @@ pub enum MyTexture {
 }
 
 impl MyTexture {
-    const fn top(&self) -> Option<UvTexture> {
+    pub const fn top(&self) -> Option<UvTexture> {
         match self {
             MyTexture::Transparent => None,
             MyTexture::AllSides(texture) => Some(*texture),
@@ -272,7 +393,7 @@ impl MyTexture {
         }
     }
 
-    const fn bottom(&self) -> Option<UvTexture> {
+    pub const fn bottom(&self) -> Option<UvTexture> {
         match self {
             MyTexture::Transparent => None,
             MyTexture::AllSides(texture) => Some(*texture),
@@ -280,7 +401,7 @@ impl MyTexture {
         }
     }
 
-    const fn px(&self) -> Option<UvTexture> {
+    pub const fn px(&self) -> Option<UvTexture> {
         match self {
             MyTexture::Transparent => None,
             MyTexture::AllSides(texture) => Some(*texture),
@@ -288,17 +409,28 @@ impl MyTexture {
         }
     }
 
-    const fn pz(&self) -> Option<UvTexture> {
+    pub const fn pz(&self) -> Option<UvTexture> {
         self.px()
     }
 
-    const fn nx(&self) -> Option<UvTexture> {
+    pub const fn nx(&self) -> Option<UvTexture> {
         self.px()
     }
 
-    const fn nz(&self) -> Option<UvTexture> {
+    pub const fn nz(&self) -> Option<UvTexture> {
         self.px()
     }
+
+    pub const fn face(&self, face: Face) -> Option<UvTexture> {
+        match face {
+            Face::Top => self.top(),
+            Face::Bottom => self.bottom(),
+            Face::Px => self.px(),
+            Face::Nx => self.nx(),
+            Face::Pz => self.pz(),
+            Face::Nz => self.nz(),
+        }
+    }
 }
 
 // TODO: Make texture depend on connected block
@@ -314,114 +446,148 @@ const fn my_texture(bs: &BlockState, _conn: &ConnectedBlocks) -> MyTexture {
         },
         BlockType::Stone => MyTexture::AllSides(UvTexture::STONE),
         BlockType::Sand => MyTexture::AllSides(UvTexture::SAND),
+        BlockType::Gravel => MyTexture::AllSides(UvTexture::GRAVEL),
+        BlockType::Torch => MyTexture::AllSides(UvTexture::TORCH),
+        BlockType::Water => MyTexture::AllSides(UvTexture::WATER),
+        BlockType::Lava => MyTexture::AllSides(UvTexture::LAVA),
+        BlockType::Wheat => MyTexture::AllSides(UvTexture::WHEAT),
+        BlockType::CraftingTable => MyTexture::AllSides(UvTexture::CRAFTING_TABLE),
     }
 }
 
-#[derive(Default, Clone, PartialEq)]
+/// Texture shown for the item in the inventory and when it is dropped
+pub const fn item_icon(item: Item) -> Option<UvTexture> {
+    match item {
+        Item::Block(block_type) => my_texture(&BlockState::new(block_type), &ConnectedBlocks::EMPTY).px(),
+        Item::Stick => Some(UvTexture::STICK),
+        Item::Wheat => Some(UvTexture::WHEAT_ITEM),
+        Item::Seeds => Some(UvTexture::SEEDS),
+    }
+}
+
+/// Shape of a non-cube block in block coordinates 0.0..=1.0
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BlockBox {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl BlockBox {
+    pub const TORCH: BlockBox = BlockBox {
+        min: vec3(7. / 16., 0., 7. / 16.),
+        max: vec3(9. / 16., 10. / 16., 9. / 16.),
+    };
+
+    /// Crops grow taller with every stage
+    pub const fn crop(age: u8) -> BlockBox {
+        let stages = ticks::MAX_CROP_AGE as f32 + 1.;
+        let height = (age as f32 + 1.).min(stages) / stages;
+        BlockBox { min: vec3(2. / 16., 0., 2. / 16.), max: vec3(14. / 16., height, 14. / 16.) }
+    }
+
+    /// Fluid surface `height` blocks above the bottom of the block
+    pub const fn fluid(height: f32) -> BlockBox {
+        BlockBox { min: vec3(0., 0., 0.), max: vec3(1., height, 1.) }
+    }
+}
+
+const fn block_box(state: &BlockState) -> Option<BlockBox> {
+    match state.block_type {
+        BlockType::Torch => Some(BlockBox::TORCH),
+        BlockType::Wheat => Some(BlockBox::crop(state.level)),
+        _ => None,
+    }
+}
+
+/// Set of visible faces of a block
+#[derive(Default, Clone, Copy, PartialEq, Eq)]
+pub struct Faces(u8);
+
+impl Faces {
+    pub const NONE: Faces = Faces(0);
+    pub const ALL: Faces = Faces(0b111111);
+
+    pub const fn contains(&self, face: Face) -> bool {
+        self.0 & (1 << face.index()) != 0
+    }
+
+    pub fn insert(&mut self, face: Face) {
+        self.0 |= 1 << face.index();
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn iter(self) -> impl Iterator<Item = Face> {
+        Face::ALL.into_iter().filter(move |face| self.contains(*face))
+    }
+}
+
+impl Debug for Faces {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (face, c) in Face::ALL.into_iter().zip(['T', 'B', 'X', 'x', 'Z', 'z']) {
+            write!(f, "{}", if self.contains(face) { c } else { ' ' })?;
+        }
+        Ok(())
+    }
+}
+
+/// How much ambient occlusion darkens a vertex, indexed by [`FaceLight::ao`]
+const AO_SHADE: [f32; 4] = [0.5, 0.7, 0.85, 1.0];
+
+/// Smooth light and ambient occlusion of the 4 vertices of a face, in the order the face's vertices are built
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FaceLight {
+    /// 0.0..=1.0, averaged over the transparent blocks touching the vertex in front of the face
+    pub sky: [f32; 4],
+    /// Same as `sky`, for block light
+    pub block: [f32; 4],
+    /// 0..=3, 0 is a vertex in the corner between two blocks
+    pub ao: [u8; 4],
+}
+
+impl FaceLight {
+    pub const FULL: FaceLight = FaceLight { sky: [1.0; 4], block: [0.0; 4], ao: [3; 4] };
+
+    /// Vertex color with ambient occlusion applied, see [`encode_light`]
+    pub fn color(&self, vertex: usize) -> [u8; 4] {
+        let ao = AO_SHADE[self.ao[vertex] as usize];
+        encode_light(self.sky[vertex] * ao, self.block[vertex] * ao)
+    }
+
+    /// Light darkened by [`face_shade`]
+    pub fn with_face_shade(mut self, face: Face) -> Self {
+        self.sky = self.sky.map(|sky| sky * face_shade(face));
+        self.block = self.block.map(|block| block * face_shade(face));
+        self
+    }
+
+    /// Quad is split along the other diagonal, so the occlusion is interpolated the same way on every face
+    pub fn flipped(&self) -> bool {
+        self.ao[0] + self.ao[2] < self.ao[1] + self.ao[3]
+    }
+}
+
+#[derive(Default, Clone, Copy, PartialEq)]
 pub enum BlockModel {
     #[default]
     Empty,
-    NonCube,
-
-    // 1 texture on 1 visible side of the block
-    Top(UvTexture),
-    Bottom(UvTexture),
-    Px(UvTexture),
-    Nx(UvTexture),
-    Pz(UvTexture),
-    Nz(UvTexture),
-
-    // 1 texture on 2 visible sides of the block
-    TopPx(UvTexture),
-    TopNx(UvTexture),
-    TopPz(UvTexture),
-    TopNz(UvTexture),
-    BottomPx(UvTexture),
-    BottomNx(UvTexture),
-    BottomPz(UvTexture),
-    BottomNz(UvTexture),
-    PxPz(UvTexture),
-    PxNz(UvTexture),
-    NxPz(UvTexture),
-    NxNz(UvTexture),
-
-    // 2 textures on 2 visible sides of the block
-    TopPxDouble(UvTexture, UvTexture),
-    TopNxDouble(UvTexture, UvTexture),
-    TopPzDouble(UvTexture, UvTexture),
-    TopNzDouble(UvTexture, UvTexture),
-    BottomPxDouble(UvTexture, UvTexture),
-    BottomNxDouble(UvTexture, UvTexture),
-    BottomPzDouble(UvTexture, UvTexture),
-    BottomNzDouble(UvTexture, UvTexture),
-
-    // 1 texture on 3 visible sides of the block
-    TopPxPz(UvTexture),
-    TopPxNz(UvTexture),
-    TopNxPz(UvTexture),
-    TopNxNz(UvTexture),
-    BottomPxPz(UvTexture),
-    BottomPxNz(UvTexture),
-    BottomNxPz(UvTexture),
-    BottomNxNz(UvTexture),
-
-    // 2 textures on 3 visible sides of the block
-    TopPxPzDouble(UvTexture, UvTexture),
-    TopPxNzDouble(UvTexture, UvTexture),
-    TopNxPzDouble(UvTexture, UvTexture),
-    TopNxNzDouble(UvTexture, UvTexture),
-    BottomPxPzDouble(UvTexture, UvTexture),
-    BottomPxNzDouble(UvTexture, UvTexture),
-    BottomNxPzDouble(UvTexture, UvTexture),
-    BottomNxNzDouble(UvTexture, UvTexture),
+    /// Box inside the block with all faces visible, lit by the block's own light
+    NonCube(BlockBox, MyTexture, Light),
+    /// Visible faces, indexed by [`Face::index`] for light
+    Cube(Faces, MyTexture, [FaceLight; 6]),
+    /// Translucent faces drawn after the terrain, the top is lowered to the height of the fluid
+    Fluid(Faces, MyTexture, Light, f32),
 }
 
 impl Debug for BlockModel {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::Empty | Self::NonCube => write!(f, "[   ]"),
-            Self::Top(_) => write!(f, "[+  ]"),
-            Self::Bottom(_) => write!(f, "[-  ]"),
-            Self::Px(_) => write!(f, "[ + ]"),
-            Self::Nx(_) => write!(f, "[ - ]"),
-            Self::Pz(_) => write!(f, "[  +]"),
-            Self::Nz(_) => write!(f, "[  -]"),
-            Self::TopPx(_) => write!(f, "[++ ]"),
-            Self::TopNx(_) => write!(f, "[+- ]"),
-            Self::TopPz(_) => write!(f, "[+ +]"),
-            Self::TopNz(_) => write!(f, "[+ -]"),
-            Self::PxPz(_) => write!(f, "[+ +]"),
-            Self::PxNz(_) => write!(f, "[+ -]"),
-            Self::NxPz(_) => write!(f, "[- +]"),
-            Self::NxNz(_) => write!(f, "[- -]"),
-            Self::BottomPx(_) => write!(f, "[-+ ]"),
-            Self::BottomNx(_) => write!(f, "[-- ]"),
-            Self::BottomPz(_) => write!(f, "[- +]"),
-            Self::BottomNz(_) => write!(f, "[- -]"),
-            Self::TopPxDouble(_, _) => write!(f, "(++ )"),
-            Self::TopNxDouble(_, _) => write!(f, "(+- )"),
-            Self::TopPzDouble(_, _) => write!(f, "(+ +)"),
-            Self::TopNzDouble(_, _) => write!(f, "(+ -)"),
-            Self::BottomPxDouble(_, _) => write!(f, "(-+ )"),
-            Self::BottomNxDouble(_, _) => write!(f, "(-- )"),
-            Self::BottomPzDouble(_, _) => write!(f, "(- +)"),
-            Self::BottomNzDouble(_, _) => write!(f, "(- -)"),
-            Self::TopPxPz(_) => write!(f, "[+++]"),
-            Self::TopPxNz(_) => write!(f, "[++-]"),
-            Self::TopNxPz(_) => write!(f, "[+-+]"),
-            Self::TopNxNz(_) => write!(f, "[+--]"),
-            Self::BottomPxPz(_) => write!(f, "[-++]"),
-            Self::BottomPxNz(_) => write!(f, "[-+-]"),
-            Self::BottomNxPz(_) => write!(f, "[--+]"),
-            Self::BottomNxNz(_) => write!(f, "[---]"),
-            Self::TopPxPzDouble(_, _) => write!(f, "(+++)"),
-            Self::TopPxNzDouble(_, _) => write!(f, "(++-)"),
-            Self::TopNxPzDouble(_, _) => write!(f, "(+-+)"),
-            Self::TopNxNzDouble(_, _) => write!(f, "(+--)"),
-            Self::BottomPxPzDouble(_, _) => write!(f, "(-++)"),
-            Self::BottomPxNzDouble(_, _) => write!(f, "(-+-)"),
-            Self::BottomNxPzDouble(_, _) => write!(f, "(--+)"),
-            Self::BottomNxNzDouble(_, _) => write!(f, "(---)"),
+            Self::Empty => write!(f, "[      ]"),
+            Self::NonCube(..) => write!(f, "[ box  ]"),
+            Self::Cube(faces, ..) => write!(f, "[{:?}]", faces),
+            Self::Fluid(faces, ..) => write!(f, "~{:?}~", faces),
         }
     }
 }
@@ -433,7 +599,7 @@ impl Debug for ChunkModel {
                 for z in 0..CHUNK_SIZE_16 {
                     self.get(x, y, z).fmt(f)?;
                 }
-                write!(f, "\n")?;
+                writeln!(f)?;
             }
             write!(f, "\n\n")?;
         }
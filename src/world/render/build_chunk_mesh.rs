@@ -1,111 +1,174 @@
 use macroquad::models::Vertex;
 
 use super::*;
+use crate::world::entity::{EntityId, EntityKind};
 
 #[path = "mesh.rs"]
 mod mesh;
 use mesh::*;
 
-#[rustfmt::skip]
 pub fn build_chunk_meshes(
     chunks: impl IntoIterator<Item = (ChunkPos, ChunkModel)>,
     atlas: Option<Texture2D>,
-) -> impl Iterator<Item = Mesh> {
-    
+) -> impl Iterator<Item = (ChunkPos, Vec<Mesh>)> {
+    chunks.into_iter().map(move |(chunk_pos, chunk_model)| {
+        let meshes = build_chunk_mesh(chunk_pos, &chunk_model, atlas.clone());
+        (chunk_pos, meshes)
+    })
+}
+
+/// Chunk may need several meshes, because macroquad limits the size of one draw call
+pub fn build_chunk_mesh(chunk_pos: ChunkPos, chunk_model: &ChunkModel, atlas: Option<Texture2D>) -> Vec<Mesh> {
+    build_lod_mesh(chunk_pos, chunk_model, Lod::Full, atlas)
+}
+
+/// Translucent fluid faces of the chunk, drawn after every opaque mesh
+#[rustfmt::skip]
+pub fn build_fluid_mesh(chunk_pos: ChunkPos, chunk_model: &ChunkModel, atlas: Option<Texture2D>) -> Vec<Mesh> {
+
     let mut meshes = Meshes::new(atlas);
 
-    for (chunk_pos, chunk_model) in chunks {
+    if chunk_model.is_empty() {
+        return meshes.into_iter().collect();
+    }
+
+    let world_pos: BlockPos = chunk_pos.into();
+
+    for y in 0..CHUNK_SIZE_16 {
+        for x in 0..CHUNK_SIZE_16 {
+            for z in 0..CHUNK_SIZE_16 {
+                let BlockModel::Fluid(faces, texture, light, height) = *chunk_model.get(x, y, z) else { continue };
+
+                let block_pos = world_pos + BlockPos::new(x as isize, y as isize, z as isize);
 
-        if chunk_model.is_empty() {
-            return meshes.into_iter();
+                for face in faces.iter() {
+                    if let Some(texture) = texture.face(face) {
+                        meshes.extend_with_box(block_pos, texture, light_color(light, face), BlockBox::fluid(height), &[face_vert(face)]);
+                    }
+                }
+            }
         }
+    }
 
-        let world_pos: BlockPos = chunk_pos.into();
-
-        for y in 0..CHUNK_SIZE_16 {
-            for x in 0..CHUNK_SIZE_16 {
-                for z in 0..CHUNK_SIZE_16 {
-                    let block_model: &BlockModel = chunk_model.get(x, y, z);
-
-                    let block_pos = BlockPos {
-                        x: x as isize + world_pos.x,
-                        y: y as isize + world_pos.y,
-                        z: z as isize + world_pos.z,
-                    };
-
-                    use BlockModel::*;
-
-                    match *block_model {
-                        Empty | NonCube => {}
-
-                        Top(texture) => meshes.extend_with(block_pos, texture, &[top_vert]),
-                        Bottom(texture) => meshes.extend_with(block_pos, texture, &[bottom_vert]),
-                        Px(texture) => meshes.extend_with(block_pos, texture, &[px_vert]),
-                        Nx(texture) => meshes.extend_with(block_pos, texture, &[nx_vert]),
-                        Pz(texture) => meshes.extend_with(block_pos, texture, &[pz_vert]),
-                        Nz(texture) => meshes.extend_with(block_pos, texture, &[nz_vert]),
-
-                        TopPx(texture) => meshes.extend_with(block_pos, texture, &[top_vert, px_vert]),
-                        TopNx(texture) => meshes.extend_with(block_pos, texture, &[top_vert, nx_vert]),
-                        TopPz(texture) => meshes.extend_with(block_pos, texture, &[top_vert, pz_vert]),
-                        TopNz(texture) => meshes.extend_with(block_pos, texture, &[top_vert, nz_vert]),
-
-                        BottomPx(texture) => meshes.extend_with(block_pos, texture, &[bottom_vert, px_vert]),
-                        BottomNx(texture) => meshes.extend_with(block_pos, texture, &[bottom_vert, nx_vert]),
-                        BottomPz(texture) => meshes.extend_with(block_pos, texture, &[bottom_vert, pz_vert]),
-                        BottomNz(texture) => meshes.extend_with(block_pos, texture, &[bottom_vert, nz_vert]),
-
-                        PxPz(texture) => meshes.extend_with(block_pos, texture, &[px_vert, pz_vert]),
-                        PxNz(texture) => meshes.extend_with(block_pos, texture, &[px_vert, nz_vert]),
-                        NxPz(texture) => meshes.extend_with(block_pos, texture, &[nx_vert, pz_vert]),
-                        NxNz(texture) => meshes.extend_with(block_pos, texture, &[nx_vert, nz_vert]),
-
-                        TopPxDouble(_, _) => todo!(),
-                        TopNxDouble(_, _) => todo!(),
-                        TopPzDouble(_, _) => todo!(),
-                        TopNzDouble(_, _) => todo!(),
-                        BottomPxDouble(_, _) => todo!(),
-                        BottomNxDouble(_, _) => todo!(),
-                        BottomPzDouble(_, _) => todo!(),
-                        BottomNzDouble(_, _) => todo!(),
-
-                        TopPxPz(texture) => meshes.extend_with(block_pos, texture, &[top_vert, px_vert, pz_vert]),
-                        TopNxPz(texture) => meshes.extend_with(block_pos, texture, &[top_vert, nx_vert, pz_vert]),
-                        TopPxNz(texture) => meshes.extend_with(block_pos, texture, &[top_vert, px_vert, nz_vert]),
-                        TopNxNz(texture) => meshes.extend_with(block_pos, texture, &[top_vert, nx_vert, nz_vert]),
-
-                        BottomPxPz(texture) => meshes.extend_with(block_pos, texture, &[bottom_vert, px_vert, pz_vert]),
-                        BottomNxPz(texture) => meshes.extend_with(block_pos, texture, &[bottom_vert, nx_vert, pz_vert]),
-                        BottomPxNz(texture) => meshes.extend_with(block_pos, texture, &[bottom_vert, px_vert, nz_vert]),
-                        BottomNxNz(texture) => meshes.extend_with(block_pos, texture, &[bottom_vert, nx_vert, nz_vert]),
-
-                        TopPxPzDouble(_, _) => todo!(),
-                        TopPxNzDouble(_, _) => todo!(),
-                        TopNxPzDouble(_, _) => todo!(),
-                        TopNxNzDouble(_, _) => todo!(),
-
-                        BottomPxPzDouble(_, _) => todo!(),
-                        BottomPxNzDouble(_, _) => todo!(),
-                        BottomNxPzDouble(_, _) => todo!(),
-                        BottomNxNzDouble(_, _) => todo!(),
-                    };
+    meshes.into_iter().collect()
+}
+
+/// Entities move off the block grid, so they are meshed every frame, lit by the block they are in
+pub fn build_entities_mesh(world: &World, hidden: Option<EntityId>, atlas: Option<Texture2D>) -> Vec<Mesh> {
+    let mut meshes = Meshes::new(atlas);
+
+    for (id, entity) in world.entities().iter() {
+        if Some(id) == hidden {
+            continue;
+        }
+
+        let (texture, min, size) = match &entity.kind {
+            EntityKind::FallingBlock(state) => {
+                (my_texture(state, &ConnectedBlocks::EMPTY), entity.pos - vec3(0.5, 0.0, 0.5), Vec3::ONE)
+            }
+            EntityKind::Player => {
+                let aabb = entity.aabb();
+                (MyTexture::AllSides(UvTexture::PLAYER), aabb.min, aabb.max - aabb.min)
+            }
+            EntityKind::Item { stack, age } => {
+                let Some(icon) = item_icon(stack.item) else { continue };
+                let aabb = entity.aabb();
+                let bob = 0.1 + (*age as f32 * 0.15).sin() * 0.05;
+                (MyTexture::AllSides(icon), aabb.min + vec3(0.0, bob, 0.0), aabb.max - aabb.min)
+            }
+        };
+
+        let block_pos = BlockPos::from_vec3(min);
+        let offset = min - min.floor();
+        let block_box = BlockBox { min: offset, max: offset + size };
+
+        let light = world.light(BlockPos::from_vec3(entity.pos + vec3(0.0, 0.5, 0.0)));
+
+        for face in Face::ALL {
+            if let Some(texture) = texture.face(face) {
+                meshes.extend_with_box(block_pos, texture, light_color(light, face), block_box, &[face_vert(face)]);
+            }
+        }
+    }
+
+    meshes.into_iter().collect()
+}
+
+/// Mesh of a model built by [`build_lod_model`], every cell is scaled to `lod.scale()` blocks
+#[rustfmt::skip]
+pub fn build_lod_mesh(chunk_pos: ChunkPos, chunk_model: &ChunkModel, lod: Lod, atlas: Option<Texture2D>) -> Vec<Mesh> {
+
+    let mut meshes = Meshes::new(atlas);
+
+    if chunk_model.is_empty() {
+        return meshes.into_iter().collect();
+    }
+
+    let world_pos: BlockPos = chunk_pos.into();
+    let origin = vec3(world_pos.x as f32, world_pos.y as f32, world_pos.z as f32);
+
+    let scale = lod.scale();
+    let n_cells = CHUNK_SIZE_16 / scale;
+
+    for y in 0..n_cells {
+        for x in 0..n_cells {
+            for z in 0..n_cells {
+                let block_pos = match lod {
+                    Lod::Full => world_pos + BlockPos::new(x as isize, y as isize, z as isize),
+                    // Scaled around the chunk origin below
+                    _ => BlockPos::new(x as isize, y as isize, z as isize),
+                };
+
+                match *chunk_model.get(x, y, z) {
+                    BlockModel::Empty | BlockModel::Fluid(..) => {}
+                    BlockModel::NonCube(block_box, texture, light) => {
+                        for face in Face::ALL {
+                            if let Some(texture) = texture.face(face) {
+                                meshes.extend_with_box(block_pos, texture, light_color(light, face), block_box, &[face_vert(face)]);
+                            }
+                        }
+                    }
+                    BlockModel::Cube(faces, texture, light) => {
+                        for face in faces.iter() {
+                            if let Some(texture) = texture.face(face) {
+                                meshes.extend_with(block_pos, texture, light[face.index()].with_face_shade(face), &[face_vert(face)]);
+                            }
+                        }
+                    }
                 }
             }
         }
-    };
-    meshes.into_iter()
+    }
+
+    if lod != Lod::Full {
+        meshes.transform_positions(|pos| origin + pos * scale as f32);
+    }
+
+    meshes.into_iter().collect()
+}
+
+const fn face_vert(face: Face) -> fn(BlockPos, UvTexture) -> [Vertex; 4] {
+    match face {
+        Face::Top => top_vert,
+        Face::Bottom => bottom_vert,
+        Face::Px => px_vert,
+        Face::Nx => nx_vert,
+        Face::Pz => pz_vert,
+        Face::Nz => nz_vert,
+    }
 }
 
 #[rustfmt::skip]
 const fn top_vert(pos: BlockPos, texture: UvTexture) -> [Vertex; 4] {
     let BlockPos { x, y, z } = pos;
     let (x, y, z) = (x as f32, y as f32, z as f32);
+    let normal = face_normal(Face::Top);
 
     [
-        vertex(vec3(0. + x, 1. + y, 0. + z), texture.low_left()),
-        vertex(vec3(1. + x, 1. + y, 0. + z), texture.low_right()),
-        vertex(vec3(1. + x, 1. + y, 1. + z), texture.up_right()),
-        vertex(vec3(0. + x, 1. + y, 1. + z), texture.low_right()),
+        vertex(vec3(0. + x, 1. + y, 0. + z), texture.low_left(), normal),
+        vertex(vec3(1. + x, 1. + y, 0. + z), texture.low_right(), normal),
+        vertex(vec3(1. + x, 1. + y, 1. + z), texture.up_right(), normal),
+        vertex(vec3(0. + x, 1. + y, 1. + z), texture.up_left(), normal),
     ]
 }
 
@@ -113,12 +176,13 @@ const fn top_vert(pos: BlockPos, texture: UvTexture) -> [Vertex; 4] {
 const fn bottom_vert(pos: BlockPos, texture: UvTexture) -> [Vertex; 4] {
     let BlockPos { x, y, z } = pos;
     let (x, y, z) = (x as f32, y as f32, z as f32);
+    let normal = face_normal(Face::Bottom);
 
     [
-        vertex(vec3(0. + x, 0. + y, 0. + z), texture.low_left()),
-        vertex(vec3(1. + x, 0. + y, 0. + z), texture.low_right()),
-        vertex(vec3(1. + x, 0. + y, 1. + z), texture.up_right()),
-        vertex(vec3(0. + x, 0. + y, 1. + z), texture.low_right()),
+        vertex(vec3(0. + x, 0. + y, 0. + z), texture.low_left(), normal),
+        vertex(vec3(1. + x, 0. + y, 0. + z), texture.low_right(), normal),
+        vertex(vec3(1. + x, 0. + y, 1. + z), texture.up_right(), normal),
+        vertex(vec3(0. + x, 0. + y, 1. + z), texture.up_left(), normal),
     ]
 }
 
@@ -126,12 +190,13 @@ const fn bottom_vert(pos: BlockPos, texture: UvTexture) -> [Vertex; 4] {
 const fn px_vert(pos: BlockPos, texture: UvTexture) -> [Vertex; 4] {
     let BlockPos { x, y, z } = pos;
     let (x, y, z) = (x as f32, y as f32, z as f32);
+    let normal = face_normal(Face::Px);
 
     [
-        vertex(vec3(1. + x, 0. + y, 0. + z), texture.low_left()),
-        vertex(vec3(1. + x, 0. + y, 1. + z), texture.low_right()),
-        vertex(vec3(1. + x, 1. + y, 1. + z), texture.up_right()),
-        vertex(vec3(1. + x, 1. + y, 0. + z), texture.up_left()),
+        vertex(vec3(1. + x, 0. + y, 0. + z), texture.low_left(), normal),
+        vertex(vec3(1. + x, 0. + y, 1. + z), texture.low_right(), normal),
+        vertex(vec3(1. + x, 1. + y, 1. + z), texture.up_right(), normal),
+        vertex(vec3(1. + x, 1. + y, 0. + z), texture.up_left(), normal),
     ]
 }
 
@@ -139,12 +204,13 @@ const fn px_vert(pos: BlockPos, texture: UvTexture) -> [Vertex; 4] {
 const fn nx_vert(pos: BlockPos, texture: UvTexture) -> [Vertex; 4] {
     let BlockPos { x, y, z } = pos;
     let (x, y, z) = (x as f32, y as f32, z as f32);
+    let normal = face_normal(Face::Nx);
 
     [
-        vertex(vec3(0. + x, 0. + y, 0. + z), texture.low_right()),
-        vertex(vec3(0. + x, 0. + y, 1. + z), texture.low_left()),
-        vertex(vec3(0. + x, 1. + y, 1. + z), texture.up_left()),
-        vertex(vec3(0. + x, 1. + y, 0. + z), texture.up_right()),
+        vertex(vec3(0. + x, 0. + y, 0. + z), texture.low_right(), normal),
+        vertex(vec3(0. + x, 0. + y, 1. + z), texture.low_left(), normal),
+        vertex(vec3(0. + x, 1. + y, 1. + z), texture.up_left(), normal),
+        vertex(vec3(0. + x, 1. + y, 0. + z), texture.up_right(), normal),
     ]
 }
 
@@ -152,12 +218,13 @@ const fn nx_vert(pos: BlockPos, texture: UvTexture) -> [Vertex; 4] {
 const fn pz_vert(pos: BlockPos, texture: UvTexture) -> [Vertex; 4] {
     let BlockPos { x, y, z } = pos;
     let (x, y, z) = (x as f32, y as f32, z as f32);
+    let normal = face_normal(Face::Pz);
 
     [
-        vertex(vec3(0. + x, 0. + y, 1. + z), texture.low_right()),
-        vertex(vec3(1. + x, 0. + y, 1. + z), texture.low_left()),
-        vertex(vec3(1. + x, 1. + y, 1. + z), texture.up_left()),
-        vertex(vec3(0. + x, 1. + y, 1. + z), texture.up_right()),
+        vertex(vec3(0. + x, 0. + y, 1. + z), texture.low_right(), normal),
+        vertex(vec3(1. + x, 0. + y, 1. + z), texture.low_left(), normal),
+        vertex(vec3(1. + x, 1. + y, 1. + z), texture.up_left(), normal),
+        vertex(vec3(0. + x, 1. + y, 1. + z), texture.up_right(), normal),
     ]
 }
 
@@ -165,11 +232,12 @@ const fn pz_vert(pos: BlockPos, texture: UvTexture) -> [Vertex; 4] {
 const fn nz_vert(pos: BlockPos, texture: UvTexture) -> [Vertex; 4] {
     let BlockPos { x, y, z } = pos;
     let (x, y, z) = (x as f32, y as f32, z as f32);
+    let normal = face_normal(Face::Nz);
 
     [
-        vertex(vec3(0. + x, 0. + y, 0. + z), texture.low_right()),
-        vertex(vec3(1. + x, 0. + y, 0. + z), texture.low_left()),
-        vertex(vec3(1. + x, 1. + y, 0. + z), texture.up_left()),
-        vertex(vec3(0. + x, 1. + y, 0. + z), texture.up_right()),
+        vertex(vec3(0. + x, 0. + y, 0. + z), texture.low_right(), normal),
+        vertex(vec3(1. + x, 0. + y, 0. + z), texture.low_left(), normal),
+        vertex(vec3(1. + x, 1. + y, 0. + z), texture.up_left(), normal),
+        vertex(vec3(0. + x, 1. + y, 0. + z), texture.up_right(), normal),
     ]
 }
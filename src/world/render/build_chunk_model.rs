@@ -1,31 +1,43 @@
 use super::*;
 
+/// Model doesn't depend on the camera, so it stays valid until the chunk or its neighbours change.
+///
+/// ```
+/// # use cubic_game::world::*;
+/// # use cubic_game::world::render::*;
+/// let mut world = World::new();
+/// world.insert_chunk(ChunkPos::new(0, 0, 0), Chunk::EMPTY);
+/// world.insert_chunk(ChunkPos::new(1, 0, 0), Chunk::EMPTY);
+///
+/// world.set_block(BlockPos::new(15, 0, 0), BlockState::STONE);
+/// world.set_block(BlockPos::new(16, 0, 0), BlockState::STONE);
+///
+/// let pos = ChunkPos::new(0, 0, 0);
+/// let model = build_chunk_model(world.chunk(pos).unwrap(), &world.connected_chunks(pos));
+///
+/// let BlockModel::Cube(faces, ..) = model.get(15, 0, 0) else { panic!() };
+/// assert!(!faces.contains(Face::Px));
+/// assert_eq!(faces.iter().count(), 5);
+///
+/// // Block across the chunk border occludes the +x vertices of the top face
+/// world.set_block(BlockPos::new(16, 1, 0), BlockState::STONE);
+/// let model = build_chunk_model(world.chunk(pos).unwrap(), &world.connected_chunks(pos));
+///
+/// let BlockModel::Cube(_, _, light) = model.get(15, 0, 0) else { panic!() };
+/// assert_eq!(light[Face::Top.index()].ao, [3, 2, 2, 3]);
+///
+/// // Surface of a lone water source is a bit below the top of the block
+/// world.set_block(BlockPos::new(4, 0, 4), BlockState::WATER);
+/// let model = build_chunk_model(world.chunk(pos).unwrap(), &world.connected_chunks(pos));
+///
+/// let BlockModel::Fluid(faces, _, _, height) = model.get(4, 0, 4) else { panic!() };
+/// assert!(faces.contains(Face::Top));
+/// assert!(*height < 1.0);
+/// ```
 #[rustfmt::skip]
-pub fn build_chunk_model(
-    player_pos: Vec3, player_front: Vec3, chunk_pos: ChunkPos, 
-    chunk: &Chunk, conn: &ConnectedChunks
-) -> ChunkModel {
+pub fn build_chunk_model(chunk: &Chunk, conn: &ConnectedChunks) -> ChunkModel {
 
-    let ConnectedChunks { top, bottom, px, nx, pz, nz } = conn;
-    let chunk_plus_connected = ChunkPlusConnected { chunk, top, bottom, px, nx, pz, nz };
-
-    let chunk_pos: BlockPos = chunk_pos.into();
-
-    let ch_pos: Vec3 = vec3(
-        chunk_pos.x as f32 + CHUNK_SIZE_16 as f32 / 2.0,
-        chunk_pos.y as f32 + CHUNK_SIZE_16 as f32 / 2.0,
-        chunk_pos.z as f32 + CHUNK_SIZE_16 as f32 / 2.0,
-    );
-
-    let chunk_view_vec: Vec3 = ch_pos - player_pos;
-
-    let angle: f32 = player_front.angle_between(chunk_view_vec);
-
-    let distance: f32 = player_pos.distance(ch_pos);
-
-    if (angle > 65f32.to_radians()) && distance > 2. * CHUNK_SIZE_16 as f32 {
-        return ChunkModel::EMPTY;
-    }
+    let chunk_plus_connected = ChunkPlusConnected { chunk, conn };
 
     let mut this_chunk_model = ChunkModel::default();
 
@@ -37,214 +49,138 @@ pub fn build_chunk_model(
                 if block_state.block_type == BlockType::Air { continue; }
 
                 let conn = chunk_plus_connected.connected_blocks(x, y, z);
-                let my_texture: MyTexture = my_texture(&block_state, &conn);
-
-                let block_pos = chunk_pos + BlockPos { x: x as isize, y: y as isize, z: z as isize };
-
-                let sides = which_3_sides_of_block_are_visible(block_pos, player_pos);
-
-                let block_model: BlockModel = match sides {
-                    ThreeSides::TopPxPz => {
-                        let (top, px, pz) = (conn.top, conn.px, conn.pz);
-
-                        match (top.is_empty(), px.is_empty(), pz.is_empty()) {
-                            (true, true, true) => BlockModel::TopPxPz(my_texture.px().unwrap()),
-
-                            (true, false, true) => BlockModel::TopPz(my_texture.px().unwrap()),
-                            (true, true, false) => BlockModel::TopPx(my_texture.px().unwrap()),
-                            (false, true, true) => BlockModel::PxPz(my_texture.px().unwrap()),
-
-                            (true, false, false) => BlockModel::Top(my_texture.px().unwrap()),
-                            (false, true, false) => BlockModel::Px(my_texture.px().unwrap()),
-                            (false, false, true) => BlockModel::Pz(my_texture.px().unwrap()),
-
-                            (false, false, false) => BlockModel::Empty,
-                        }
-                    },
-                    ThreeSides::TopPxNz => {
-                        let (top, px, nz) = (conn.top, conn.px, conn.nz);
-
-                        match (top.is_empty(), px.is_empty(), nz.is_empty()) {
-                            (true, true, true) => BlockModel::TopPxNz(my_texture.px().unwrap()),
-
-                            (true, false, true) => BlockModel::TopNz(my_texture.px().unwrap()),
-                            (true, true, false) => BlockModel::TopPx(my_texture.px().unwrap()),
-                            (false, true, true) => BlockModel::PxNz(my_texture.px().unwrap()),
-
-                            (true, false, false) => BlockModel::Top(my_texture.px().unwrap()),
-                            (false, true, false) => BlockModel::Px(my_texture.px().unwrap()),
-                            (false, false, true) => BlockModel::Nz(my_texture.px().unwrap()),
-
-                            (false, false, false) => BlockModel::Empty,
-                        }
-                    },
-                    ThreeSides::TopNxPz => {
-                        let (top, nx, pz) = (conn.top, conn.nx, conn.pz);
-
-                        match (top.is_empty(), nx.is_empty(), pz.is_empty()) {
-                            (true, true, true) => BlockModel::TopNxPz(my_texture.px().unwrap()),
-
-                            (true, false, true) => BlockModel::TopPz(my_texture.px().unwrap()),
-                            (true, true, false) => BlockModel::TopNx(my_texture.px().unwrap()),
-                            (false, true, true) => BlockModel::NxPz(my_texture.px().unwrap()),
-
-                            (true, false, false) => BlockModel::Top(my_texture.px().unwrap()),
-                            (false, true, false) => BlockModel::Nx(my_texture.px().unwrap()),
-                            (false, false, true) => BlockModel::Pz(my_texture.px().unwrap()),
-
-                            (false, false, false) => BlockModel::Empty,
-                        }
-                    },
-                    ThreeSides::TopNxNz => {
-                        let (top, nx, nz) = (conn.top, conn.nx, conn.nz);
-
-                        match (top.is_empty(), nx.is_empty(), nz.is_empty()) {
-                            (true, true, true) => BlockModel::TopNxNz(my_texture.px().unwrap()),
-
-                            (true, false, true) => BlockModel::TopNz(my_texture.px().unwrap()),
-                            (true, true, false) => BlockModel::TopNx(my_texture.px().unwrap()),
-                            (false, true, true) => BlockModel::NxNz(my_texture.px().unwrap()),
-
-                            (true, false, false) => BlockModel::Top(my_texture.px().unwrap()),
-                            (false, true, false) => BlockModel::Nx(my_texture.px().unwrap()),
-                            (false, false, true) => BlockModel::Nz(my_texture.px().unwrap()),
-
-                            (false, false, false) => BlockModel::Empty,
-                        }
-                    },
-                    ThreeSides::BottomPxPz => {
-                        let (bottom, px, pz) = (conn.bottom, conn.px, conn.pz);
-
-                        match (bottom.is_empty(), px.is_empty(), pz.is_empty()) {
-                            (true, true, true) => BlockModel::BottomPxPz(my_texture.px().unwrap()),
-
-                            (true, false, true) => BlockModel::BottomPz(my_texture.px().unwrap()),
-                            (true, true, false) => BlockModel::BottomPx(my_texture.px().unwrap()),
-                            (false, true, true) => BlockModel::PxPz(my_texture.px().unwrap()),
-
-                            (true, false, false) => BlockModel::Bottom(my_texture.px().unwrap()),
-                            (false, true, false) => BlockModel::Px(my_texture.px().unwrap()),
-                            (false, false, true) => BlockModel::Pz(my_texture.px().unwrap()),
-
-                            (false, false, false) => BlockModel::Empty,
-                        }
-                    },
-                    ThreeSides::BottomPxNz => {
-                        let (bottom, px, nz) = (conn.bottom, conn.px, conn.nz);
-
-                        match (bottom.is_empty(), px.is_empty(), nz.is_empty()) {
-                            (true, true, true) => BlockModel::BottomPxNz(my_texture.px().unwrap()),
-
-                            (true, false, true) => BlockModel::BottomNz(my_texture.px().unwrap()),
-                            (true, true, false) => BlockModel::BottomPx(my_texture.px().unwrap()),
-                            (false, true, true) => BlockModel::PxNz(my_texture.px().unwrap()),
-
-                            (true, false, false) => BlockModel::Bottom(my_texture.px().unwrap()),
-                            (false, true, false) => BlockModel::Px(my_texture.px().unwrap()),
-                            (false, false, true) => BlockModel::Nz(my_texture.px().unwrap()),
-
-                            (false, false, false) => BlockModel::Empty,
-                        }
-                    },
-                    ThreeSides::BottomNxPz => {
-                        let (bottom, nx, pz) = (conn.bottom, conn.nx, conn.pz);
-
-                        match (bottom.is_empty(), nx.is_empty(), pz.is_empty()) {
-                            (true, true, true) => BlockModel::BottomNxPz(my_texture.px().unwrap()),
-
-                            (true, false, true) => BlockModel::BottomPz(my_texture.px().unwrap()),
-                            (true, true, false) => BlockModel::BottomNx(my_texture.px().unwrap()),
-                            (false, true, true) => BlockModel::NxPz(my_texture.px().unwrap()),
-
-                            (true, false, false) => BlockModel::Bottom(my_texture.px().unwrap()),
-                            (false, true, false) => BlockModel::Nx(my_texture.px().unwrap()),
-                            (false, false, true) => BlockModel::Pz(my_texture.px().unwrap()),
-
-                            (false, false, false) => BlockModel::Empty,
-                        }
-                    },
-                    ThreeSides::BottomNxNz => {
-                        let (bottom, nx, nz) = (conn.bottom, conn.nx, conn.nz);
-
-                        match (bottom.is_empty(), nx.is_empty(), nz.is_empty()) {
-                            (true, true, true) => BlockModel::BottomNxNz(my_texture.px().unwrap()),
-
-                            (true, false, true) => BlockModel::BottomNz(my_texture.px().unwrap()),
-                            (true, true, false) => BlockModel::BottomNx(my_texture.px().unwrap()),
-                            (false, true, true) => BlockModel::NxNz(my_texture.px().unwrap()),
-
-                            (true, false, false) => BlockModel::Bottom(my_texture.px().unwrap()),
-                            (false, true, false) => BlockModel::Nx(my_texture.px().unwrap()),
-                            (false, false, true) => BlockModel::Nz(my_texture.px().unwrap()),
-
-                            (false, false, false) => BlockModel::Empty,
-                        }
-                    },
-                };
-
-                this_chunk_model.set(x, y, z, block_model);
+                let my_texture: MyTexture = my_texture(block_state, &conn);
+
+                if let Some(fluid) = block_state.fluid() {
+                    let faces = fluid_faces(&conn, fluid);
+                    if faces.is_empty() { continue; }
+
+                    let light = chunk.light.get(x, y, z);
+                    let height = fluid_height(block_state, conn.top);
+                    this_chunk_model.set(x, y, z, BlockModel::Fluid(faces, my_texture, light, height));
+                    continue;
+                }
+
+                if let Some(block_box) = block_box(block_state) {
+                    let light = chunk.light.get(x, y, z);
+                    this_chunk_model.set(x, y, z, BlockModel::NonCube(block_box, my_texture, light));
+                    continue;
+                }
+
+                let faces = visible_faces(&conn);
+                if faces.is_empty() { continue; }
+
+                let pos = (x as isize, y as isize, z as isize);
+                let mut light = [FaceLight::FULL; 6];
+                for face in faces.iter() {
+                    light[face.index()] = face_light(&chunk_plus_connected, pos, face);
+                }
+
+                this_chunk_model.set(x, y, z, BlockModel::Cube(faces, my_texture, light));
             }
         }
     }
     this_chunk_model
 }
 
-fn which_3_sides_of_block_are_visible(block_pos: BlockPos, player_pos: Vec3) -> ThreeSides {
-    let block_pos = vec3(block_pos.x as f32, block_pos.y as f32, block_pos.z as f32);
+fn visible_faces(conn: &ConnectedBlocks) -> Faces {
+    let ConnectedBlocks { top, bottom, px, nx, pz, nz } = conn;
 
-    let (player_above, player_px, player_pz) = (
-        player_pos.y > block_pos.y, player_pos.x > block_pos.x, player_pos.z > block_pos.z 
-    );
-    ThreeSides::from_logic(player_above, player_px, player_pz)
-}
-
-enum ThreeSides {
-    TopPxPz, TopPxNz, TopNxPz, TopNxNz,
-    BottomPxPz, BottomPxNz, BottomNxPz, BottomNxNz,
-}
+    let mut faces = Faces::NONE;
 
-impl ThreeSides {
-    fn from_logic(player_above: bool, player_px: bool, player_pz: bool) -> Self {
-        match (player_above, player_px, player_pz) {
-            (true, true, true) => ThreeSides::TopPxPz,
-            (true, false, true) => ThreeSides::TopNxPz,
-            (true, true, false) => ThreeSides::TopPxNz,
-            (true, false, false) => ThreeSides::TopNxNz,
-            (false, true, true) => ThreeSides::BottomPxPz,
-            (false, false, true) => ThreeSides::BottomNxPz,
-            (false, true, false) => ThreeSides::BottomPxNz,
-            (false, false, false) => ThreeSides::BottomNxNz,
+    for (face, neighbour) in Face::ALL.into_iter().zip([top, bottom, px, nx, pz, nz]) {
+        if !neighbour.is_opaque() {
+            faces.insert(face);
         }
     }
-    pub fn top_visible(&self) -> bool {
-        match self {
-            ThreeSides::TopPxPz | ThreeSides::TopPxNz |
-            ThreeSides::TopNxPz | ThreeSides::TopNxNz => true,
-            _ => false,
-        }
-    }
-    pub fn bottom_visible(&self) -> bool {
-        !self.top_visible()
-    }
-    pub fn px_visible(&self) -> bool {
-        match self {
-            ThreeSides::TopPxPz | ThreeSides::TopPxNz | 
-            ThreeSides::BottomPxPz | ThreeSides::BottomPxNz => true,
-            _ => false
+    faces
+}
+
+/// Fluid is seen through everything but opaque blocks and the same fluid
+fn fluid_faces(conn: &ConnectedBlocks, fluid: Fluid) -> Faces {
+    let ConnectedBlocks { top, bottom, px, nx, pz, nz } = conn;
+
+    let mut faces = Faces::NONE;
+
+    for (face, neighbour) in Face::ALL.into_iter().zip([top, bottom, px, nx, pz, nz]) {
+        let hidden = neighbour.fluid() == Some(fluid) || (neighbour.is_opaque() && face != Face::Top);
+        if !hidden {
+            faces.insert(face);
         }
     }
-    pub fn nx_visible(&self) -> bool {
-        !self.px_visible()
+    faces
+}
+
+/// Fluid under the same fluid fills the whole block, otherwise the surface sinks the farther it flows
+fn fluid_height(state: &BlockState, above: &BlockState) -> f32 {
+    if above.fluid().is_some() && above.fluid() == state.fluid() {
+        return 1.0;
     }
-    pub fn pz_visible(&self) -> bool {
-        match self {
-            ThreeSides::TopNxPz | ThreeSides::TopPxPz | 
-            ThreeSides::BottomPxPz | ThreeSides::BottomNxPz => true,
-            _ => false
-        }
+    match state.level {
+        0 | fluid::FALLING => 8. / 9.,
+        level => (8 - level.min(7)) as f32 / 9.,
     }
-    pub fn is_nz_visible(&self) -> bool {
-        !self.pz_visible()
+}
+
+/// Corners of the face in the same order as its vertices in [`build_chunk_mesh`]
+#[rustfmt::skip]
+const fn face_corners(face: Face) -> [(isize, isize, isize); 4] {
+    match face {
+        Face::Top => [(0, 1, 0), (1, 1, 0), (1, 1, 1), (0, 1, 1)],
+        Face::Bottom => [(0, 0, 0), (1, 0, 0), (1, 0, 1), (0, 0, 1)],
+        Face::Px => [(1, 0, 0), (1, 0, 1), (1, 1, 1), (1, 1, 0)],
+        Face::Nx => [(0, 0, 0), (0, 0, 1), (0, 1, 1), (0, 1, 0)],
+        Face::Pz => [(0, 0, 1), (1, 0, 1), (1, 1, 1), (0, 1, 1)],
+        Face::Nz => [(0, 0, 0), (1, 0, 0), (1, 1, 0), (0, 1, 0)],
     }
 }
 
+/// Every vertex is occluded by the 2 side blocks and the corner block touching it in front of the face,
+/// and lit by the transparent ones of them together with the block in front of the face.
+fn face_light(chunk: &ChunkPlusConnected<'_, '_>, (x, y, z): (isize, isize, isize), face: Face) -> FaceLight {
+    let (nx, ny, nz) = face.offset();
+    let at = |(dx, dy, dz): (isize, isize, isize)| chunk.neighbourhood(x + nx + dx, y + ny + dy, z + nz + dz);
+
+    let mut face_light = FaceLight::FULL;
+
+    for (vertex, (cx, cy, cz)) in face_corners(face).into_iter().enumerate() {
+        // Towards the vertex along the two axes of the face
+        let towards = |corner: isize, normal: isize| match (normal, corner) {
+            (0, 0) => -1,
+            (0, _) => 1,
+            _ => 0,
+        };
+        let (dx, dy, dz) = (towards(cx, nx), towards(cy, ny), towards(cz, nz));
+
+        let (side_a, side_b) = match (nx, ny) {
+            (0, 0) => ((dx, 0, 0), (0, dy, 0)),
+            (0, _) => ((dx, 0, 0), (0, 0, dz)),
+            _ => ((0, dy, 0), (0, 0, dz)),
+        };
+
+        let front = at((0, 0, 0));
+        let side_a = at(side_a);
+        let side_b = at(side_b);
+        let corner = at((dx, dy, dz));
+
+        let (a, b, c) = (side_a.0.is_opaque(), side_b.0.is_opaque(), corner.0.is_opaque());
+
+        face_light.ao[vertex] = match a && b {
+            true => 0,
+            false => 3 - a as u8 - b as u8 - c as u8,
+        };
+
+        // Corner can't be seen through two opaque sides
+        let lit = [(front, true), (side_a, !a), (side_b, !b), (corner, !c && !(a && b))];
+        let (sky, block, count) = lit
+            .iter()
+            .filter(|(_, transparent)| *transparent)
+            .fold((0.0, 0.0, 0.0), |(sky, block, count), ((_, light), _)| {
+                (sky + light.sky_brightness(), block + light.block_brightness(), count + 1.0)
+            });
+
+        face_light.sky[vertex] = sky / count;
+        face_light.block[vertex] = block / count;
+    }
+    face_light
+}
@@ -0,0 +1,116 @@
+use super::*;
+
+/// Level of detail of a chunk mesh, every level doubles the size of a cell
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Lod {
+    #[default]
+    Full,
+    X2,
+    X4,
+    X8,
+}
+
+impl Lod {
+    pub const ALL: [Lod; 4] = [Lod::Full, Lod::X2, Lod::X4, Lod::X8];
+
+    /// Size of one cell in blocks
+    pub const fn scale(self) -> usize {
+        1 << self as usize
+    }
+
+    /// Every `lod_distance` chunks away from the camera the detail halves
+    ///
+    /// ```
+    /// # use cubic_game::world::render::Lod;
+    /// assert_eq!(Lod::for_distance(3, 4), Lod::Full);
+    /// assert_eq!(Lod::for_distance(4, 4), Lod::X2);
+    /// assert_eq!(Lod::for_distance(100, 4), Lod::X8);
+    /// ```
+    pub fn for_distance(chunk_distance: usize, lod_distance: usize) -> Lod {
+        let level = chunk_distance / lod_distance.max(1);
+        Lod::ALL[level.min(Lod::ALL.len() - 1)]
+    }
+
+    pub const fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// Downsampled model where one cell of `lod.scale()` blocks is solid if any of its blocks is opaque.
+///
+/// Only cells `0..16 / lod.scale()` of the model are used. Faces on the chunk border are always kept,
+/// so there are no gaps next to chunks with a different level of detail.
+#[rustfmt::skip]
+pub fn build_lod_model(chunk: &Chunk, lod: Lod) -> ChunkModel {
+    let scale = lod.scale();
+    let n_cells = CHUNK_SIZE_16 / scale;
+
+    let mut cells: Vec<Option<BlockState>> = Vec::with_capacity(n_cells * n_cells * n_cells);
+
+    for y in 0..n_cells {
+        for x in 0..n_cells {
+            for z in 0..n_cells {
+                cells.push(downsample(chunk, (x * scale, y * scale, z * scale), scale));
+            }
+        }
+    }
+
+    let cell = |x: usize, y: usize, z: usize| &cells[(y * n_cells + x) * n_cells + z];
+
+    let mut model = ChunkModel::default();
+
+    for y in 0..n_cells {
+        for x in 0..n_cells {
+            for z in 0..n_cells {
+                let Some(state) = cell(x, y, z) else { continue };
+
+                let last = n_cells - 1;
+                let open = |inside: bool, (x, y, z): (usize, usize, usize)| !inside || cell(x, y, z).is_none();
+
+                let mut faces = Faces::NONE;
+
+                for (face, inside, neighbour) in [
+                    (Face::Top, y < last, (x, y + 1, z)),
+                    (Face::Bottom, y > 0, (x, y.wrapping_sub(1), z)),
+                    (Face::Px, x < last, (x + 1, y, z)),
+                    (Face::Nx, x > 0, (x.wrapping_sub(1), y, z)),
+                    (Face::Pz, z < last, (x, y, z + 1)),
+                    (Face::Nz, z > 0, (x, y, z.wrapping_sub(1))),
+                ] {
+                    if open(inside, neighbour) {
+                        faces.insert(face);
+                    }
+                }
+
+                if faces.is_empty() { continue; }
+
+                // Distant chunks are seen from above, so they are lit as if by the sky
+                let texture = my_texture(state, &ConnectedBlocks::EMPTY);
+                model.set(x, y, z, BlockModel::Cube(faces, texture, [FaceLight::FULL; 6]));
+            }
+        }
+    }
+    model
+}
+
+/// Most common opaque block of the cell
+fn downsample(chunk: &Chunk, (x0, y0, z0): (usize, usize, usize), scale: usize) -> Option<BlockState> {
+    let mut counts: Vec<(&BlockState, usize)> = Vec::new();
+
+    for y in y0..y0 + scale {
+        for x in x0..x0 + scale {
+            for z in z0..z0 + scale {
+                let state = chunk.get(x, y, z);
+                if !state.is_opaque() {
+                    continue;
+                }
+                match counts.iter_mut().find(|(s, _)| *s == state) {
+                    Some((_, count)) => *count += 1,
+                    None => counts.push((state, 1)),
+                }
+            }
+        }
+    }
+
+    counts.into_iter().max_by_key(|(_, count)| *count).map(|(state, _)| state.clone())
+}
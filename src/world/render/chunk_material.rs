@@ -0,0 +1,118 @@
+use macroquad::miniquad::{BlendFactor, BlendState, BlendValue, Equation};
+
+use super::*;
+
+pub const CHUNK_VERTEX_SHADER_PATH: &str = "assets/chunk.vert";
+pub const CHUNK_FRAGMENT_SHADER_PATH: &str = "assets/chunk.frag";
+
+/// Fog starts at this fraction of the render distance
+const FOG_START: f32 = 0.6;
+
+/// Material for chunk meshes, which decodes sky and block light from vertex colors and blends distant terrain into fog.
+#[derive(Clone)]
+pub struct ChunkMaterial {
+    material: Material,
+    /// Same shaders with alpha blending and without depth writes, for fluids
+    translucent: Material,
+}
+
+/// Values the chunk shader needs every frame
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChunkUniforms {
+    pub camera_pos: Vec3,
+    /// Unit vector pointing towards the sun
+    pub sun_direction: Vec3,
+    /// Color of sky light at the current time of day, black at night
+    pub sky_tint: Vec3,
+    /// Color distant terrain fades into, usually the sky color
+    pub fog_color: Color,
+    /// In blocks
+    pub render_distance: f32,
+}
+
+impl ChunkMaterial {
+    /// Loads and compiles the shaders from [`CHUNK_VERTEX_SHADER_PATH`] and [`CHUNK_FRAGMENT_SHADER_PATH`].
+    pub async fn load() -> Result<Self, String> {
+        let vertex = load_shader_source(CHUNK_VERTEX_SHADER_PATH).await?;
+        let fragment = load_shader_source(CHUNK_FRAGMENT_SHADER_PATH).await?;
+
+        Self::from_sources(&vertex, &fragment)
+    }
+
+    pub fn from_sources(vertex: &str, fragment: &str) -> Result<Self, String> {
+        let material = load_chunk_material(vertex, fragment, PipelineParams {
+            depth_write: true,
+            depth_test: Comparison::LessOrEqual,
+            ..Default::default()
+        })?;
+
+        let translucent = load_chunk_material(vertex, fragment, PipelineParams {
+            depth_write: false,
+            depth_test: Comparison::LessOrEqual,
+            color_blend: Some(BlendState::new(
+                Equation::Add,
+                BlendFactor::Value(BlendValue::SourceAlpha),
+                BlendFactor::OneMinusValue(BlendValue::SourceAlpha),
+            )),
+            ..Default::default()
+        })?;
+
+        Ok(Self { material, translucent })
+    }
+
+    /// Meshes drawn until [`gl_use_default_material`] is called use this material
+    pub fn apply(&self, uniforms: &ChunkUniforms) {
+        for material in [&self.material, &self.translucent] {
+            material.set_uniform("CameraPos", uniforms.camera_pos);
+            material.set_uniform("SunDirection", uniforms.sun_direction.normalize_or_zero());
+            material.set_uniform("SkyTint", uniforms.sky_tint);
+            material.set_uniform("FogColor", uniforms.fog_color.to_vec());
+            material.set_uniform("FogStart", uniforms.render_distance * FOG_START);
+            material.set_uniform("FogEnd", uniforms.render_distance);
+        }
+
+        gl_use_material(&self.material);
+    }
+
+    /// Switches to blending with the uniforms of the last [`ChunkMaterial::apply`]
+    pub fn apply_translucent(&self) {
+        gl_use_material(&self.translucent);
+    }
+}
+
+fn load_chunk_material(vertex: &str, fragment: &str, pipeline_params: PipelineParams) -> Result<Material, String> {
+    load_material(
+        ShaderSource::Glsl { vertex, fragment },
+        MaterialParams {
+            pipeline_params,
+            uniforms: vec![
+                UniformDesc::new("CameraPos", UniformType::Float3),
+                UniformDesc::new("SunDirection", UniformType::Float3),
+                UniformDesc::new("SkyTint", UniformType::Float3),
+                UniformDesc::new("FogColor", UniformType::Float4),
+                UniformDesc::new("FogStart", UniformType::Float1),
+                UniformDesc::new("FogEnd", UniformType::Float1),
+            ],
+            ..Default::default()
+        },
+    )
+    .map_err(describe_shader_error)
+}
+
+async fn load_shader_source(path: &str) -> Result<String, String> {
+    load_string(path)
+        .await
+        .map_err(|err| format!("Unable to load chunk shader {}: {}", path, err))
+}
+
+fn describe_shader_error(err: macroquad::Error) -> String {
+    match err {
+        macroquad::Error::ShaderError(ShaderError::CompilationError { shader_type, error_message }) => {
+            format!("Chunk {:?} shader doesn't compile:\n{}", shader_type, error_message)
+        }
+        macroquad::Error::ShaderError(ShaderError::LinkError(error_message)) => {
+            format!("Chunk shaders don't link:\n{}", error_message)
+        }
+        err => format!("Unable to create chunk material: {}", err),
+    }
+}
@@ -0,0 +1,223 @@
+use std::collections::{HashMap, HashSet};
+
+use super::*;
+
+/// Chunks meshed in one frame at most, the rest wait for the next frames
+pub const MESHES_PER_FRAME: usize = 8;
+
+/// Number of chunk meshes drawn and skipped during the last frame
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct DrawStats {
+    pub drawn: usize,
+    pub culled: usize,
+}
+
+/// Size of the meshes of the currently selected levels of detail
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct MeshStats {
+    /// Chunks with anything to draw
+    pub chunks: usize,
+    pub meshes: usize,
+    pub vertices: usize,
+    pub indices: usize,
+}
+
+/// Meshes of one chunk, levels of detail are built when they are first needed
+#[derive(Default)]
+struct ChunkLods {
+    lods: [Option<Vec<Mesh>>; Lod::ALL.len()],
+    /// Fluids, built together with [`Lod::Full`]
+    translucent: Vec<Mesh>,
+    selected: Lod,
+}
+
+impl ChunkLods {
+    fn selected(&self) -> &[Mesh] {
+        self.lods[self.selected.index()].as_deref().unwrap_or_default()
+    }
+
+    /// Distant chunks are drawn without fluids
+    fn selected_translucent(&self) -> &[Mesh] {
+        match self.selected {
+            Lod::Full => &self.translucent,
+            _ => &[],
+        }
+    }
+}
+
+/// Meshes of every loaded chunk
+pub struct ChunkMeshes {
+    chunks: HashMap<ChunkPos, ChunkLods>,
+    /// Changed chunks, drawn with their old meshes until they are meshed again
+    outdated: HashSet<ChunkPos>,
+    /// Chunks left without the mesh they need after the last update
+    queued: usize,
+    visibility: VisibilityGraph,
+    atlas: Option<Texture2D>,
+}
+
+impl ChunkMeshes {
+    pub fn new(atlas: Option<Texture2D>) -> Self {
+        Self { chunks: HashMap::new(), outdated: HashSet::new(), queued: 0, visibility: VisibilityGraph::default(), atlas }
+    }
+
+    /// Chunks waiting to be meshed in the next frames
+    pub fn queued(&self) -> usize {
+        self.queued
+    }
+
+    /// Takes chunks marked dirty in the world and builds the level of detail every chunk needs now,
+    /// at most [`MESHES_PER_FRAME`] of them nearest to the camera first. Without `lod_distance` all chunks are drawn in full detail.
+    ///
+    /// ```
+    /// # use cubic_game::world::*;
+    /// # use cubic_game::world::render::*;
+    /// let mut world = World::new();
+    /// for x in 0..10 {
+    ///     let mut chunk = Chunk::EMPTY;
+    ///     chunk.blocks[0].fill(BlockState::STONE);
+    ///     world.insert_chunk(ChunkPos::new(x, 0, 0), chunk);
+    /// }
+    ///
+    /// let mut meshes = ChunkMeshes::new(None);
+    /// meshes.update(&mut world, ChunkPos::new(0, 0, 0), None);
+    /// assert_eq!(meshes.queued(), 10 - MESHES_PER_FRAME);
+    /// assert_eq!(meshes.stats().chunks, MESHES_PER_FRAME);
+    ///
+    /// meshes.update(&mut world, ChunkPos::new(0, 0, 0), None);
+    /// assert_eq!((meshes.queued(), meshes.stats().chunks), (0, 10));
+    /// ```
+    pub fn update(&mut self, world: &mut World, camera_chunk: ChunkPos, lod_distance: Option<u32>) {
+        for pos in world.take_dirty() {
+            let Some(chunk) = world.chunk(pos) else { continue };
+
+            self.visibility.insert(pos, chunk);
+            self.chunks.entry(pos).or_default();
+            self.outdated.insert(pos);
+        }
+
+        let mut queue = Vec::new();
+
+        for (pos, chunk_lods) in &mut self.chunks {
+            if !world.contains_chunk(*pos) {
+                continue;
+            }
+
+            let distance = pos.x.abs_diff(camera_chunk.x).max(pos.z.abs_diff(camera_chunk.z));
+            let lod = match lod_distance {
+                Some(lod_distance) => Lod::for_distance(distance, lod_distance as usize),
+                None => Lod::Full,
+            };
+
+            // The old level stays drawn until the new one is built
+            if chunk_lods.lods[lod.index()].is_some() && !self.outdated.contains(pos) {
+                chunk_lods.selected = lod;
+                continue;
+            }
+            queue.push((distance.max(pos.y.abs_diff(camera_chunk.y)), *pos, lod));
+        }
+
+        queue.sort_unstable_by_key(|(distance, _, _)| *distance);
+        self.queued = queue.len().saturating_sub(MESHES_PER_FRAME);
+
+        for (_, pos, lod) in queue.into_iter().take(MESHES_PER_FRAME) {
+            let (Some(chunk), Some(chunk_lods)) = (world.chunk(pos), self.chunks.get_mut(&pos)) else { continue };
+
+            if self.outdated.remove(&pos) {
+                *chunk_lods = ChunkLods::default();
+            }
+
+            let meshes = match lod {
+                Lod::Full => {
+                    let model = build_chunk_model(chunk, &world.connected_chunks(pos));
+                    chunk_lods.translucent = build_fluid_mesh(pos, &model, self.atlas.clone());
+                    build_chunk_mesh(pos, &model, self.atlas.clone())
+                }
+                _ => build_lod_mesh(pos, &build_lod_model(chunk, lod), lod, self.atlas.clone()),
+            };
+            chunk_lods.lods[lod.index()] = Some(meshes);
+            chunk_lods.selected = lod;
+        }
+    }
+
+    /// Meshes of the currently selected level of detail
+    pub fn meshes(&self) -> impl Iterator<Item = &Mesh> {
+        self.chunks.values().flat_map(ChunkLods::selected)
+    }
+
+    /// Fluid meshes included
+    pub fn stats(&self) -> MeshStats {
+        let mut stats = MeshStats::default();
+
+        for chunk_lods in self.chunks.values() {
+            let meshes: Vec<&Mesh> = chunk_lods.selected().iter().chain(chunk_lods.selected_translucent()).collect();
+            if meshes.is_empty() {
+                continue;
+            }
+
+            stats.chunks += 1;
+            stats.meshes += meshes.len();
+            stats.vertices += meshes.iter().map(|mesh| mesh.vertices.len()).sum::<usize>();
+            stats.indices += meshes.iter().map(|mesh| mesh.indices.len()).sum::<usize>();
+        }
+        stats
+    }
+
+    /// Draws chunks within render distance which intersect the camera frustum
+    /// and can be seen from the camera chunk through transparent blocks.
+    /// Fluids are drawn last with the translucent `material`, farthest chunks first.
+    pub fn draw(&self, camera: &Camera3D, render_distance: u32, material: &ChunkMaterial) -> DrawStats {
+        let frustum = Frustum::from_camera(camera);
+        let camera_chunk = BlockPos::from_vec3(camera.position).chunk_pos();
+
+        // Chunks missing from the graph count as air, so the search has to stop a bit above and below the world
+        let (min_y, max_y) = self.visibility.y_range().unwrap_or((camera_chunk.y, camera_chunk.y));
+        let y_range = (min_y - 1).min(camera_chunk.y)..=(max_y + 1).max(camera_chunk.y);
+
+        let visible = self.visibility.visible_from(camera_chunk, |pos| {
+            let (min, max) = pos.aabb();
+
+            y_range.contains(&pos.y)
+                && pos.x.abs_diff(camera_chunk.x) <= render_distance as usize
+                && pos.z.abs_diff(camera_chunk.z) <= render_distance as usize
+                && frustum.intersects_aabb(min, max)
+        });
+
+        let mut stats = DrawStats::default();
+
+        for (pos, chunk_lods) in &self.chunks {
+            let meshes = chunk_lods.selected();
+            if meshes.is_empty() {
+                continue;
+            }
+
+            if !visible.contains(pos) {
+                stats.culled += 1;
+                continue;
+            }
+
+            for mesh in meshes {
+                draw_mesh(mesh);
+            }
+            stats.drawn += 1;
+        }
+
+        let mut translucent: Vec<_> = self
+            .chunks
+            .iter()
+            .filter(|(pos, chunk_lods)| visible.contains(pos) && !chunk_lods.selected_translucent().is_empty())
+            .collect();
+        translucent.sort_by(|(a, _), (b, _)| {
+            let distance = |pos: &ChunkPos| pos.center().distance_squared(camera.position);
+            distance(b).total_cmp(&distance(a))
+        });
+
+        material.apply_translucent();
+        for (_, chunk_lods) in translucent {
+            for mesh in chunk_lods.selected_translucent() {
+                draw_mesh(mesh);
+            }
+        }
+        stats
+    }
+}
@@ -0,0 +1,56 @@
+use super::*;
+
+/// View frustum as 6 planes `(normal, distance)` pointing inwards.
+///
+/// ```
+/// # use cubic_game::world::render::Frustum;
+/// # use macroquad::prelude::*;
+/// let view = Mat4::look_at_rh(Vec3::ZERO, vec3(0., 0., -1.), Vec3::Y);
+/// let projection = Mat4::perspective_rh_gl(45f32.to_radians(), 1.0, 0.01, 1000.0);
+///
+/// let frustum = Frustum::from_matrix(projection * view);
+///
+/// assert!(frustum.intersects_aabb(vec3(-1., -1., -11.), vec3(1., 1., -9.)));
+/// assert!(!frustum.intersects_aabb(vec3(-1., -1., 9.), vec3(1., 1., 11.)));
+/// assert!(!frustum.intersects_aabb(vec3(50., -1., -11.), vec3(52., 1., -9.)));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Frustum {
+    planes: [Vec4; 6],
+}
+
+impl Frustum {
+    /// Extracts planes from the `projection * view` matrix
+    #[rustfmt::skip]
+    pub fn from_matrix(matrix: Mat4) -> Self {
+        let (r0, r1, r2, r3) = (matrix.row(0), matrix.row(1), matrix.row(2), matrix.row(3));
+
+        let planes = [
+            r3 + r0, r3 - r0, // left, right
+            r3 + r1, r3 - r1, // bottom, top
+            r3 + r2, r3 - r2, // near, far
+        ];
+
+        Self { planes: planes.map(|plane| plane / plane.truncate().length()) }
+    }
+
+    pub fn from_camera(camera: &Camera3D) -> Self {
+        Self::from_matrix(camera.matrix())
+    }
+
+    /// Conservative: may return `true` for some boxes near the frustum corners
+    pub fn intersects_aabb(&self, min: Vec3, max: Vec3) -> bool {
+        self.planes.iter().all(|plane| {
+            let normal = plane.truncate();
+
+            // Corner of the box which is the furthest along the plane normal
+            let corner = vec3(
+                if normal.x >= 0. { max.x } else { min.x },
+                if normal.y >= 0. { max.y } else { min.y },
+                if normal.z >= 0. { max.z } else { min.z },
+            );
+
+            normal.dot(corner) + plane.w >= 0.
+        })
+    }
+}
@@ -26,11 +26,57 @@ impl Meshes {
         &mut self,
         block_pos: BlockPos,
         texture: UvTexture,
+        face_light: FaceLight,
         funcs: &[fn(BlockPos, UvTexture) -> [Vertex; 4]],
     ) {
+        let colors = [0, 1, 2, 3].map(|vertex| face_light.color(vertex));
+        let indices = match face_light.flipped() {
+            true => FLIPPED_PLANE_IND,
+            false => PLANE_IND,
+        };
+
         for func in funcs {
-            self.indices.extend(PLANE_IND.map(|i| self.vertices.len() as u16 + i));
-            self.vertices.extend(func(block_pos, texture));
+            self.push_plane(func(block_pos, texture), colors, indices);
+        }
+    }
+
+    /// Planes of a unit block are shrunk to `block_box`
+    pub fn extend_with_box(
+        &mut self,
+        block_pos: BlockPos,
+        texture: UvTexture,
+        color: [u8; 4],
+        block_box: BlockBox,
+        funcs: &[fn(BlockPos, UvTexture) -> [Vertex; 4]],
+    ) {
+        let BlockPos { x, y, z } = block_pos;
+        let origin = vec3(x as f32, y as f32, z as f32);
+        let size = block_box.max - block_box.min;
+
+        for func in funcs {
+            let plane = func(BlockPos::new(0, 0, 0), texture).map(|mut vertex| {
+                vertex.position = origin + block_box.min + vertex.position * size;
+                vertex
+            });
+            self.push_plane(plane, [color; 4], PLANE_IND);
+        }
+    }
+
+    fn push_plane(&mut self, plane: [Vertex; 4], colors: [[u8; 4]; 4], indices: [u16; 6]) {
+        // Indices are relative to the mesh this plane will end up in
+        let first = (self.vertices.len() % VERT_CAP) as u16;
+        self.indices.extend(indices.map(|i| first + i));
+
+        for (vertex, color) in plane.into_iter().zip(colors) {
+            self.vertices.push(Vertex { color, ..vertex });
+        }
+    }
+}
+
+impl Meshes {
+    pub fn transform_positions(&mut self, func: impl Fn(Vec3) -> Vec3) {
+        for vertex in &mut self.vertices {
+            vertex.position = func(vertex.position);
         }
     }
 }
@@ -41,6 +87,13 @@ const PLANE_IND: [u16; 6] = [
     0, 3, 2,
 ];
 
+/// Split along the 1-3 diagonal
+#[rustfmt::skip]
+const FLIPPED_PLANE_IND: [u16; 6] = [
+    1, 2, 3,
+    1, 0, 3,
+];
+
 impl Meshes {
 
     #[rustfmt::skip]
@@ -52,12 +105,10 @@ impl Meshes {
         let ind = indices.chunks(IND_CAP);
         let mut vert = vertices.chunks(VERT_CAP);
 
-        const N_IND_IN_FULL_MESH: usize = 3_332;
-
-        for (i, meshes_ind) in ind.enumerate() {
+        for meshes_ind in ind {
             meshes.push(Mesh { 
                 vertices: Vec::from(vert.next().unwrap()), 
-                indices: Vec::from_iter(meshes_ind.iter().map(|ind| ind - (i * N_IND_IN_FULL_MESH) as u16)), 
+                indices: Vec::from(meshes_ind), 
                 texture: texture.clone(),
             });
         }
@@ -0,0 +1,164 @@
+use macroquad::models::Vertex;
+
+use super::*;
+use crate::world::time::WorldTime;
+
+/// Everything in the sky is drawn this far from the camera, behind the terrain
+const SKY_DISTANCE: f32 = 1000.0;
+
+const DOME_SEGMENTS: usize = 16;
+/// Elevation of every ring of the dome in radians, from below the horizon up to the zenith
+const DOME_RINGS: [f32; 5] = [-0.5, 0.0, 0.3, 0.8, std::f32::consts::FRAC_PI_2];
+
+const N_STARS: usize = 400;
+
+const SUN_SIZE: f32 = 60.0;
+const MOON_SIZE: f32 = 40.0;
+const STAR_SIZE: f32 = 2.5;
+
+#[rustfmt::skip]
+const DAY_ZENITH: Color = Color { r: 0.25, g: 0.45, b: 0.85, a: 1.0 };
+#[rustfmt::skip]
+const DAY_HORIZON: Color = Color { r: 0.6, g: 0.75, b: 0.95, a: 1.0 };
+#[rustfmt::skip]
+const NIGHT_ZENITH: Color = Color { r: 0.01, g: 0.01, b: 0.04, a: 1.0 };
+#[rustfmt::skip]
+const NIGHT_HORIZON: Color = Color { r: 0.04, g: 0.04, b: 0.1, a: 1.0 };
+#[rustfmt::skip]
+const SUNSET_GLOW: Color = Color { r: 0.95, g: 0.45, b: 0.2, a: 1.0 };
+
+#[rustfmt::skip]
+const SUN_COLOR: Color = Color { r: 1.0, g: 0.95, b: 0.7, a: 1.0 };
+#[rustfmt::skip]
+const MOON_COLOR: Color = Color { r: 0.85, g: 0.87, b: 0.95, a: 1.0 };
+
+/// Sky gradient, sun, moon and stars around the camera
+#[derive(Debug, Clone)]
+pub struct Sky {
+    /// Unit vectors, the stars turn with the sun
+    stars: Vec<Vec3>,
+}
+
+impl Default for Sky {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Sky {
+    pub fn new() -> Self {
+        let mut seed: u32 = 0x5EED;
+        let mut random = move || {
+            // xorshift, stars look the same every time
+            seed ^= seed << 13;
+            seed ^= seed >> 17;
+            seed ^= seed << 5;
+            seed as f32 / u32::MAX as f32
+        };
+
+        let stars = (0..N_STARS)
+            .map(|_| vec3(random() * 2.0 - 1.0, random() * 2.0 - 1.0, random() * 2.0 - 1.0))
+            .filter(|star| star.length_squared() > 0.01)
+            .map(Vec3::normalize)
+            .collect();
+
+        Self { stars }
+    }
+
+    /// Color at the top of the sky
+    pub fn zenith_color(time: WorldTime) -> Color {
+        mix(NIGHT_ZENITH, DAY_ZENITH, time.sunlight())
+    }
+
+    /// Color at the horizon, also used for fog and the clear color
+    pub fn horizon_color(time: WorldTime) -> Color {
+        let horizon = mix(NIGHT_HORIZON, DAY_HORIZON, time.sunlight());
+        mix(horizon, SUNSET_GLOW, time.twilight() * 0.5)
+    }
+
+    /// Draws with the current camera, before the terrain
+    pub fn draw(&self, camera_pos: Vec3, time: WorldTime) {
+        draw_mesh(&dome_mesh(camera_pos, time));
+
+        let sun = time.sun_direction();
+
+        let star_alpha = (1.0 - time.sunlight() * 1.5).clamp(0.0, 1.0);
+        if star_alpha > 0.0 {
+            let color = Color { a: star_alpha, ..macroquad::color::WHITE };
+            let rotation = Quat::from_rotation_arc(Vec3::Y, sun);
+
+            let mut stars = Mesh { vertices: Vec::new(), indices: Vec::new(), texture: None };
+            for star in &self.stars {
+                push_billboard(&mut stars, camera_pos, rotation * *star * SKY_DISTANCE * 0.95, STAR_SIZE, color);
+            }
+            draw_mesh(&stars);
+        }
+
+        let mut bodies = Mesh { vertices: Vec::new(), indices: Vec::new(), texture: None };
+        push_billboard(&mut bodies, camera_pos, sun * SKY_DISTANCE * 0.9, SUN_SIZE, SUN_COLOR);
+        push_billboard(&mut bodies, camera_pos, -sun * SKY_DISTANCE * 0.9, MOON_SIZE, MOON_COLOR);
+        draw_mesh(&bodies);
+    }
+}
+
+/// Square facing the camera at `offset` from it
+fn push_billboard(mesh: &mut Mesh, camera_pos: Vec3, offset: Vec3, size: f32, color: Color) {
+    let towards = offset.normalize();
+    let side = match towards.cross(Vec3::Y).try_normalize() {
+        Some(side) => side,
+        None => Vec3::X,
+    };
+    let up = side.cross(towards);
+
+    let center = camera_pos + offset;
+    let (side, up) = (side * size / 2.0, up * size / 2.0);
+
+    let first = mesh.vertices.len() as u16;
+    for corner in [center - side - up, center + side - up, center + side + up, center - side + up] {
+        mesh.vertices.push(Vertex { position: corner, uv: Vec2::ZERO, color: color.into(), normal: Vec4::ZERO });
+    }
+    mesh.indices.extend([0, 1, 2, 0, 2, 3].map(|i| first + i));
+}
+
+fn mix(a: Color, b: Color, t: f32) -> Color {
+    Color::from_vec(a.to_vec().lerp(b.to_vec(), t))
+}
+
+/// Half sphere from the zenith colour down to the horizon colour, with a band below the horizon
+fn dome_mesh(camera_pos: Vec3, time: WorldTime) -> Mesh {
+    let zenith = Sky::zenith_color(time);
+    let horizon = Sky::horizon_color(time);
+
+    let mut vertices = Vec::with_capacity(DOME_RINGS.len() * (DOME_SEGMENTS + 1));
+    let mut indices = Vec::new();
+
+    for (ring, elevation) in DOME_RINGS.into_iter().enumerate() {
+        let color = mix(horizon, zenith, elevation.sin().max(0.0));
+
+        for segment in 0..=DOME_SEGMENTS {
+            let angle = segment as f32 / DOME_SEGMENTS as f32 * std::f32::consts::TAU;
+            let direction = vec3(angle.cos() * elevation.cos(), elevation.sin(), angle.sin() * elevation.cos());
+
+            vertices.push(Vertex {
+                position: camera_pos + direction * SKY_DISTANCE,
+                uv: Vec2::ZERO,
+                color: color.into(),
+                normal: Vec4::ZERO,
+            });
+
+            if ring > 0 && segment > 0 {
+                let row = DOME_SEGMENTS as u16 + 1;
+                let (ring, segment) = (ring as u16, segment as u16);
+
+                let up_right = ring * row + segment;
+                let up_left = up_right - 1;
+                let low_right = up_right - row;
+                let low_left = low_right - 1;
+
+                indices.extend([low_left, low_right, up_right, low_left, up_right, up_left]);
+            }
+        }
+    }
+
+    Mesh { vertices, indices, texture: None }
+}
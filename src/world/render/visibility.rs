@@ -0,0 +1,167 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use super::*;
+
+const N_BLOCKS: usize = CHUNK_SIZE_16 * CHUNK_SIZE_16 * CHUNK_SIZE_16;
+
+/// Which faces of a chunk are connected to each other through transparent blocks inside the chunk
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ChunkVisibility(u64);
+
+impl ChunkVisibility {
+    pub const NONE: ChunkVisibility = ChunkVisibility(0);
+    pub const ALL: ChunkVisibility = ChunkVisibility(u64::MAX);
+
+    pub const fn connected(&self, a: Face, b: Face) -> bool {
+        self.0 & (1 << (a.index() * 6 + b.index())) != 0
+    }
+
+    pub fn connect(&mut self, a: Face, b: Face) {
+        self.0 |= 1 << (a.index() * 6 + b.index());
+        self.0 |= 1 << (b.index() * 6 + a.index());
+    }
+
+    /// Flood fills every transparent region of the chunk and connects all faces each region touches
+    pub fn from_chunk(chunk: &Chunk) -> Self {
+        let index = |x: usize, y: usize, z: usize| (y * CHUNK_SIZE_16 + x) * CHUNK_SIZE_16 + z;
+        let last = CHUNK_SIZE_16 - 1;
+
+        let mut visibility = ChunkVisibility::NONE;
+        let mut visited = vec![false; N_BLOCKS];
+        let mut stack = Vec::new();
+
+        for y in 0..CHUNK_SIZE_16 {
+            for x in 0..CHUNK_SIZE_16 {
+                for z in 0..CHUNK_SIZE_16 {
+                    if visited[index(x, y, z)] || chunk.get(x, y, z).is_opaque() {
+                        continue;
+                    }
+
+                    let mut touched = Faces::NONE;
+
+                    visited[index(x, y, z)] = true;
+                    stack.push((x, y, z));
+
+                    while let Some((x, y, z)) = stack.pop() {
+                        #[rustfmt::skip]
+                        let neighbours = [
+                            (Face::Top, y < last, (x, y + 1, z)),
+                            (Face::Bottom, y > 0, (x, y.wrapping_sub(1), z)),
+                            (Face::Px, x < last, (x + 1, y, z)),
+                            (Face::Nx, x > 0, (x.wrapping_sub(1), y, z)),
+                            (Face::Pz, z < last, (x, y, z + 1)),
+                            (Face::Nz, z > 0, (x, y, z.wrapping_sub(1))),
+                        ];
+
+                        for (face, inside, (nx, ny, nz)) in neighbours {
+                            if !inside {
+                                touched.insert(face);
+                                continue;
+                            }
+                            if visited[index(nx, ny, nz)] || chunk.get(nx, ny, nz).is_opaque() {
+                                continue;
+                            }
+                            visited[index(nx, ny, nz)] = true;
+                            stack.push((nx, ny, nz));
+                        }
+                    }
+
+                    for a in touched.iter() {
+                        for b in touched.iter() {
+                            visibility.connect(a, b);
+                        }
+                    }
+                }
+            }
+        }
+        visibility
+    }
+}
+
+/// Face-to-face visibility of every chunk, used to skip chunks hidden behind solid terrain.
+///
+/// ```
+/// # use cubic_game::world::*;
+/// # use cubic_game::world::render::*;
+/// let mut solid = Chunk::EMPTY;
+/// solid.fill(BlockState::STONE);
+///
+/// // Air | Stone | Air
+/// let mut graph = VisibilityGraph::default();
+/// graph.insert(ChunkPos::new(0, 0, 0), &Chunk::EMPTY);
+/// graph.insert(ChunkPos::new(1, 0, 0), &solid);
+/// graph.insert(ChunkPos::new(2, 0, 0), &Chunk::EMPTY);
+///
+/// let row = |pos: ChunkPos| pos.y == 0 && pos.z == 0 && (0..3).contains(&pos.x);
+/// let visible = graph.visible_from(ChunkPos::new(0, 0, 0), row);
+///
+/// assert!(visible.contains(&ChunkPos::new(1, 0, 0)));
+/// assert!(!visible.contains(&ChunkPos::new(2, 0, 0)));
+///
+/// // Tunnel through the stone chunk
+/// let mut tunnel = solid.clone();
+/// for x in 0..16 {
+///     *tunnel.get_mut(x, 8, 8) = BlockState::AIR;
+/// }
+/// graph.insert(ChunkPos::new(1, 0, 0), &tunnel);
+///
+/// let visible = graph.visible_from(ChunkPos::new(0, 0, 0), row);
+/// assert!(visible.contains(&ChunkPos::new(2, 0, 0)));
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct VisibilityGraph {
+    chunks: HashMap<ChunkPos, ChunkVisibility>,
+}
+
+impl VisibilityGraph {
+    pub fn insert(&mut self, pos: ChunkPos, chunk: &Chunk) {
+        self.chunks.insert(pos, ChunkVisibility::from_chunk(chunk));
+    }
+
+    pub fn remove(&mut self, pos: ChunkPos) {
+        self.chunks.remove(&pos);
+    }
+
+    /// Lowest and highest chunk y
+    pub fn y_range(&self) -> Option<(isize, isize)> {
+        let min = self.chunks.keys().map(|pos| pos.y).min()?;
+        let max = self.chunks.keys().map(|pos| pos.y).max()?;
+        Some((min, max))
+    }
+
+    /// BFS from the camera chunk which only enters a chunk through a face connected to the face it came from,
+    /// and never turns back against a direction it already moved in. Chunks missing from the graph are treated as air.
+    ///
+    /// `filter` limits the search, e.g. to chunks within render distance and camera frustum.
+    pub fn visible_from(&self, camera_chunk: ChunkPos, filter: impl Fn(ChunkPos) -> bool) -> HashSet<ChunkPos> {
+        let mut visible = HashSet::from([camera_chunk]);
+        let mut queue = VecDeque::from([(camera_chunk, None::<Face>, Faces::NONE)]);
+
+        while let Some((pos, entered_through, directions)) = queue.pop_front() {
+            let visibility = self.chunks.get(&pos).copied().unwrap_or(ChunkVisibility::ALL);
+
+            for face in Face::ALL {
+                if directions.contains(face.opposite()) {
+                    continue;
+                }
+                if let Some(entered_through) = entered_through {
+                    if !visibility.connected(entered_through, face) {
+                        continue;
+                    }
+                }
+
+                let next = pos.neighbour(face);
+                if visible.contains(&next) || !filter(next) {
+                    continue;
+                }
+
+                let mut next_directions = directions;
+                next_directions.insert(face);
+
+                visible.insert(next);
+                queue.push_back((next, Some(face.opposite()), next_directions));
+            }
+        }
+        visible
+    }
+}
@@ -0,0 +1,205 @@
+use std::collections::{HashMap, HashSet};
+
+use super::light::LightLayer;
+use super::render::{ConnectedChunks, ConnectedEdges};
+use super::*;
+
+/// All loaded chunks. Chunks which need to be re-meshed are marked dirty.
+#[derive(Debug, Default)]
+pub struct World {
+    chunks: HashMap<ChunkPos, Chunk>,
+    dirty: HashSet<ChunkPos>,
+    time: WorldTime,
+    scheduled_ticks: ScheduledTicks,
+    /// State of the generator behind [`World::random`]
+    random: u64,
+    entities: Entities,
+}
+
+impl World {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn time(&self) -> WorldTime {
+        self.time
+    }
+
+    pub fn set_time(&mut self, time: WorldTime) {
+        self.time = time;
+    }
+
+    /// Advances the simulation by one tick. Time stops at the last tick instead of overflowing
+    pub fn tick(&mut self) {
+        self.time.0 = self.time.0.saturating_add(1);
+        self.tick_blocks();
+        self.tick_entities();
+    }
+
+    /// Pseudo-random number for block updates, the same sequence in every world
+    pub fn random(&mut self) -> u32 {
+        // LCG constants from Knuth's MMIX
+        self.random = self.random.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (self.random >> 32) as u32
+    }
+
+    pub fn chunk(&self, pos: ChunkPos) -> Option<&Chunk> {
+        self.chunks.get(&pos)
+    }
+
+    pub fn contains_chunk(&self, pos: ChunkPos) -> bool {
+        self.chunks.contains_key(&pos)
+    }
+
+    pub fn chunks(&self) -> impl Iterator<Item = (ChunkPos, &Chunk)> {
+        self.chunks.iter().map(|(pos, chunk)| (*pos, chunk))
+    }
+
+    pub fn n_chunks(&self) -> usize {
+        self.chunks.len()
+    }
+
+    /// Lights the chunk and marks it and its neighbours dirty, including diagonal ones, because their borders have changed
+    pub fn insert_chunk(&mut self, pos: ChunkPos, chunk: Chunk) {
+        self.chunks.insert(pos, chunk);
+
+        for x in -1..=1 {
+            for y in -1..=1 {
+                for z in -1..=1 {
+                    self.dirty.insert(ChunkPos::new(pos.x + x, pos.y + y, pos.z + z));
+                }
+            }
+        }
+
+        light::light_chunk(self, pos);
+    }
+
+    /// Changes to the blocks aren't tracked, use [`World::set_block`] or [`World::mark_dirty`]
+    pub fn chunk_mut(&mut self, pos: ChunkPos) -> Option<&mut Chunk> {
+        self.chunks.get_mut(&pos)
+    }
+
+    /// Unloaded blocks are air
+    pub fn block(&self, pos: BlockPos) -> &BlockState {
+        let (x, y, z) = pos.in_chunk();
+        match self.chunks.get(&pos.chunk_pos()) {
+            Some(chunk) => chunk.get(x, y, z),
+            None => &BlockState::AIR,
+        }
+    }
+
+    /// Updates light and schedules updates of the blocks around it. Returns `false` if the chunk isn't loaded
+    pub fn set_block(&mut self, pos: BlockPos, state: BlockState) -> bool {
+        let chunk_pos = pos.chunk_pos();
+        let (x, y, z) = pos.in_chunk();
+
+        let Some(chunk) = self.chunks.get_mut(&chunk_pos) else {
+            return false;
+        };
+        *chunk.get_mut(x, y, z) = state;
+
+        self.mark_dirty_around(pos);
+
+        light::update_light(self, pos);
+        self.schedule_ticks_around(pos);
+        true
+    }
+
+    pub fn mark_dirty(&mut self, pos: ChunkPos) {
+        self.dirty.insert(pos);
+    }
+
+    /// Marks the chunk of the block dirty, together with every neighbouring chunk it touches, diagonally as well
+    pub fn mark_dirty_around(&mut self, pos: BlockPos) {
+        let chunk_pos = pos.chunk_pos();
+        let (x, y, z) = pos.in_chunk();
+
+        let range = |n: usize| match n {
+            0 => -1..=0,
+            n if n == CHUNK_SIZE_16 - 1 => 0..=1,
+            _ => 0..=0,
+        };
+
+        for dx in range(x) {
+            for dy in range(y) {
+                for dz in range(z) {
+                    self.dirty.insert(ChunkPos::new(chunk_pos.x + dx, chunk_pos.y + dy, chunk_pos.z + dz));
+                }
+            }
+        }
+    }
+
+    /// Updates the block at `pos` after `delay` ticks, unless it is replaced by another type of block before that
+    pub fn schedule_tick(&mut self, pos: BlockPos, delay: u64) {
+        let block_type = self.block(pos).block_type;
+        self.scheduled_ticks.schedule(self.time.0.saturating_add(delay), pos, block_type);
+    }
+
+    /// Block updates scheduled up to the current tick
+    pub fn take_due_ticks(&mut self) -> Vec<(BlockPos, BlockType)> {
+        self.scheduled_ticks.take_due(self.time.0)
+    }
+
+    /// Block updates waiting for their tick
+    pub fn n_scheduled_ticks(&self) -> usize {
+        self.scheduled_ticks.len()
+    }
+
+    pub fn entities(&self) -> &Entities {
+        &self.entities
+    }
+
+    pub fn entities_mut(&mut self) -> &mut Entities {
+        &mut self.entities
+    }
+
+    /// Chunks waiting to be re-meshed, unloaded ones included
+    pub fn n_dirty(&self) -> usize {
+        self.dirty.len()
+    }
+
+    /// Loaded dirty chunks, dirty flags are cleared
+    pub fn take_dirty(&mut self) -> Vec<ChunkPos> {
+        let chunks = &self.chunks;
+        self.dirty.drain().filter(|pos| chunks.contains_key(pos)).collect()
+    }
+
+    /// Border layers of the 6 neighbouring chunks and the edges of the diagonal ones, unloaded chunks are air lit by the sky
+    #[rustfmt::skip]
+    pub fn connected_chunks(&self, pos: ChunkPos) -> ConnectedChunks {
+        let mut conn = ConnectedChunks::EMPTY;
+        let last = CHUNK_SIZE_16 - 1;
+
+        if let Some(top) = self.chunk(pos.neighbour(Face::Top)) {
+            conn.top = ChunkLayer::from_fn(|x, z| top.get(x, 0, z).clone());
+            conn.light.top = LightLayer::from_fn(|x, z| top.light.get(x, 0, z));
+        }
+        if let Some(bottom) = self.chunk(pos.neighbour(Face::Bottom)) {
+            conn.bottom = ChunkLayer::from_fn(|x, z| bottom.get(x, last, z).clone());
+            conn.light.bottom = LightLayer::from_fn(|x, z| bottom.light.get(x, last, z));
+        }
+        if let Some(px) = self.chunk(pos.neighbour(Face::Px)) {
+            conn.px = ChunkLayer::from_fn(|y, z| px.get(0, y, z).clone());
+            conn.light.px = LightLayer::from_fn(|y, z| px.light.get(0, y, z));
+        }
+        if let Some(nx) = self.chunk(pos.neighbour(Face::Nx)) {
+            conn.nx = ChunkLayer::from_fn(|y, z| nx.get(last, y, z).clone());
+            conn.light.nx = LightLayer::from_fn(|y, z| nx.light.get(last, y, z));
+        }
+        if let Some(pz) = self.chunk(pos.neighbour(Face::Pz)) {
+            conn.pz = ChunkLayer::from_fn(|x, y| pz.get(x, y, 0).clone());
+            conn.light.pz = LightLayer::from_fn(|x, y| pz.light.get(x, y, 0));
+        }
+        if let Some(nz) = self.chunk(pos.neighbour(Face::Nz)) {
+            conn.nz = ChunkLayer::from_fn(|x, y| nz.get(x, y, last).clone());
+            conn.light.nz = LightLayer::from_fn(|x, y| nz.light.get(x, y, last));
+        }
+
+        let origin: BlockPos = pos.into();
+        conn.edges = ConnectedEdges::from_fn(|x, y, z| {
+            let pos = origin + BlockPos::new(x, y, z);
+            (self.block(pos).clone(), self.light(pos))
+        });
+        conn
+    }
+}
@@ -0,0 +1,170 @@
+use std::collections::BTreeMap;
+
+use super::*;
+
+/// Blocks picked at random in every loaded chunk each tick
+pub const RANDOM_TICKS_PER_CHUNK: usize = 3;
+
+/// Grass spreads and crops grow only with at least this much sky or block light
+pub const MIN_GROWTH_LIGHT: u8 = 9;
+
+pub const MAX_CROP_AGE: u8 = 7;
+
+/// Block updates by the tick they are due
+#[derive(Debug, Default, Clone)]
+pub struct ScheduledTicks(BTreeMap<u64, Vec<(BlockPos, BlockType)>>);
+
+impl ScheduledTicks {
+    /// Update of a block is scheduled only once per tick
+    pub fn schedule(&mut self, tick: u64, pos: BlockPos, block_type: BlockType) {
+        let updates = self.0.entry(tick).or_default();
+        if !updates.iter().any(|(scheduled, _)| *scheduled == pos) {
+            updates.push((pos, block_type));
+        }
+    }
+
+    /// Updates up to `tick`, in the order they were scheduled
+    pub fn take_due(&mut self, tick: u64) -> Vec<(BlockPos, BlockType)> {
+        let mut due = Vec::new();
+        while let Some(entry) = self.0.first_entry() {
+            if *entry.key() > tick {
+                break;
+            }
+            due.extend(entry.remove());
+        }
+        due
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.values().map(Vec::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl World {
+    /// Blocks at and next to a changed block may react to it
+    pub fn schedule_ticks_around(&mut self, pos: BlockPos) {
+        for pos in std::iter::once(pos).chain(Face::ALL.map(|face| pos.neighbour(face))) {
+            if let Some(delay) = self.block(pos).block_type.tick_delay() {
+                self.schedule_tick(pos, delay);
+            }
+        }
+    }
+
+    /// Runs scheduled updates due this tick, then [`RANDOM_TICKS_PER_CHUNK`] random ones in every chunk
+    pub fn tick_blocks(&mut self) {
+        for (pos, block_type) in self.take_due_ticks() {
+            if self.block(pos).block_type == block_type {
+                update_block(self, pos);
+            }
+        }
+
+        let chunks: Vec<ChunkPos> = self.chunks().map(|(pos, _)| pos).collect();
+        for chunk_pos in chunks {
+            for _ in 0..RANDOM_TICKS_PER_CHUNK {
+                let random = self.random() as usize;
+                let size = CHUNK_SIZE_16;
+                let (x, y, z) = (random % size, random / size % size, random / size / size % size);
+
+                random_tick_block(self, BlockPos::from(chunk_pos) + BlockPos::new(x as isize, y as isize, z as isize));
+            }
+        }
+    }
+}
+
+/// Scheduled update: fluids flow, sand and gravel start falling and crops without soil break
+///
+/// ```
+/// # use cubic_game::world::*;
+/// let mut world = World::new();
+/// world.insert_chunk(ChunkPos::new(0, 0, 0), Chunk::EMPTY);
+/// world.set_block(BlockPos::new(3, 0, 3), BlockState::STONE);
+/// world.set_block(BlockPos::new(3, 6, 3), BlockState::SAND);
+///
+/// for _ in 0..40 { world.tick(); }
+///
+/// assert_eq!(world.block(BlockPos::new(3, 6, 3)), &BlockState::AIR);
+/// assert_eq!(world.block(BlockPos::new(3, 1, 3)), &BlockState::SAND);
+/// ```
+pub fn update_block(world: &mut World, pos: BlockPos) {
+    let state = world.block(pos).clone();
+
+    match state.block_type {
+        BlockType::Water | BlockType::Lava => fluid::update_fluid(world, pos),
+        block_type if block_type.has_gravity() => {
+            let below = world.block(pos.neighbour(Face::Bottom));
+            if below.is_empty() || below.fluid().is_some() {
+                world.spawn_falling_block(pos);
+            }
+        }
+        BlockType::Wheat if !is_soil(world.block(pos.neighbour(Face::Bottom))) => {
+            world.set_block(pos, BlockState::AIR);
+        }
+        _ => {}
+    }
+}
+
+/// Random update: grass spreads to lit dirt nearby and dies under opaque blocks, crops grow in the light
+///
+/// ```
+/// # use cubic_game::world::*;
+/// # use cubic_game::world::ticks::*;
+/// let mut world = World::new();
+/// world.insert_chunk(ChunkPos::new(0, 0, 0), Chunk::EMPTY);
+/// world.set_block(BlockPos::new(3, 0, 3), BlockState::DIRT);
+/// world.set_block(BlockPos::new(3, 1, 3), BlockState::WHEAT);
+///
+/// random_tick_block(&mut world, BlockPos::new(3, 1, 3));
+/// assert_eq!(world.block(BlockPos::new(3, 1, 3)).level, 1);
+///
+/// // Grass under an opaque block turns into dirt
+/// world.set_block(BlockPos::new(5, 0, 5), BlockState::GRASS);
+/// world.set_block(BlockPos::new(5, 1, 5), BlockState::STONE);
+///
+/// random_tick_block(&mut world, BlockPos::new(5, 0, 5));
+/// assert_eq!(world.block(BlockPos::new(5, 0, 5)), &BlockState::DIRT);
+/// ```
+pub fn random_tick_block(world: &mut World, pos: BlockPos) {
+    let state = world.block(pos).clone();
+
+    match state.block_type {
+        BlockType::Grass => {
+            if world.block(pos.neighbour(Face::Top)).is_opaque() {
+                world.set_block(pos, BlockState::DIRT);
+                return;
+            }
+            if light_level(world, pos.neighbour(Face::Top)) < MIN_GROWTH_LIGHT {
+                return;
+            }
+
+            // Somewhere in the 3x5x3 box around the grass, mostly below it
+            let random = world.random() as isize;
+            let target = pos + BlockPos::new(random % 3 - 1, random / 3 % 5 - 3, random / 15 % 3 - 1);
+            let above_target = target.neighbour(Face::Top);
+
+            if *world.block(target) == BlockState::DIRT
+                && !world.block(above_target).is_opaque()
+                && light_level(world, above_target) >= MIN_GROWTH_LIGHT
+            {
+                world.set_block(target, BlockState::GRASS);
+            }
+        }
+        BlockType::Wheat if state.level < MAX_CROP_AGE && light_level(world, pos) >= MIN_GROWTH_LIGHT => {
+            world.set_block(pos, BlockState::with_level(BlockType::Wheat, state.level + 1));
+        }
+        _ => {}
+    }
+}
+
+fn light_level(world: &World, pos: BlockPos) -> u8 {
+    let light = world.light(pos);
+    light.sky().max(light.block())
+}
+
+/// Crops grow on these
+fn is_soil(state: &BlockState) -> bool {
+    matches!(state.block_type, BlockType::Dirt | BlockType::Grass)
+}
@@ -0,0 +1,85 @@
+use std::f32::consts::TAU;
+
+use derive_more::{Deref, DerefMut};
+
+use super::*;
+
+pub const TICKS_PER_DAY: u64 = 24000;
+
+pub const SUNRISE: u64 = 0;
+pub const DAY: u64 = 1000;
+pub const NOON: u64 = 6000;
+pub const SUNSET: u64 = 12000;
+pub const NIGHT: u64 = 13000;
+pub const MIDNIGHT: u64 = 18000;
+
+/// Sky light is never completely dark, the moon lights the world at night
+const MOONLIGHT: f32 = 0.2;
+
+/// Ticks since the world was created. The sun rises at [`SUNRISE`] and sets at [`SUNSET`] every day.
+///
+/// ```
+/// # use cubic_game::world::time::*;
+/// let noon = WorldTime(NOON);
+/// assert!(noon.sun_direction().y > 0.99);
+/// assert_eq!(noon.daylight(), 1.0);
+///
+/// let midnight = WorldTime(10 * TICKS_PER_DAY + MIDNIGHT);
+/// assert!(midnight.sun_direction().y < -0.99);
+/// assert!(midnight.daylight() < noon.daylight());
+///
+/// let mut last = WorldTime(u64::MAX);
+/// last.set_time_of_day(NOON);
+/// assert_eq!(last, WorldTime(u64::MAX));
+/// ```
+#[derive(Debug, Default, Deref, DerefMut, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct WorldTime(pub u64);
+
+impl WorldTime {
+    /// Ticks since the start of the current day
+    pub const fn time_of_day(self) -> u64 {
+        self.0 % TICKS_PER_DAY
+    }
+
+    pub const fn day(self) -> u64 {
+        self.0 / TICKS_PER_DAY
+    }
+
+    /// Sets the time of day, keeping the day. Time never goes back, so this may move to the next day.
+    /// The time stops at the end of the last day which fits.
+    pub fn set_time_of_day(&mut self, time_of_day: u64) {
+        let time_of_day = time_of_day % TICKS_PER_DAY;
+        let day = match time_of_day < self.time_of_day() {
+            true => self.day() + 1,
+            false => self.day(),
+        };
+        self.0 = day.saturating_mul(TICKS_PER_DAY).saturating_add(time_of_day);
+    }
+
+    /// Unit vector pointing towards the sun, which rises at +x and sets at -x. The moon is opposite.
+    pub fn sun_direction(self) -> Vec3 {
+        let angle = self.time_of_day() as f32 / TICKS_PER_DAY as f32 * TAU;
+        vec3(angle.cos(), angle.sin(), 0.1).normalize()
+    }
+
+    /// 0.0 at night to 1.0 during the day, changing around sunrise and sunset
+    pub fn sunlight(self) -> f32 {
+        (self.sun_direction().y * 4.0 + 0.5).clamp(0.0, 1.0)
+    }
+
+    /// Sky light multiplier, from [`MOONLIGHT`] at night to 1.0 during the day
+    pub fn daylight(self) -> f32 {
+        MOONLIGHT + (1.0 - MOONLIGHT) * self.sunlight()
+    }
+
+    /// How close the sun is to the horizon, 1.0 at sunrise and sunset
+    pub fn twilight(self) -> f32 {
+        (1.0 - self.sun_direction().y.abs() * 4.0).clamp(0.0, 1.0)
+    }
+
+    /// Color of sky light, warmer at sunrise and sunset
+    pub fn sky_tint(self) -> Vec3 {
+        let warm = vec3(1.0, 0.75, 0.55);
+        Vec3::ONE.lerp(warm, self.twilight() * 0.6) * self.daylight()
+    }
+}